@@ -0,0 +1,187 @@
+use iamlisp::{repl, Error, Expression, Interpreter, List, Value};
+use std::io::{IsTerminal, Read};
+
+const EXIT_RUNTIME_ERROR: i32 = 1;
+const EXIT_PARSE_ERROR: i32 = 2;
+const EXIT_USAGE_ERROR: i32 = 64;
+const EXIT_INPUT_ERROR: i32 = 66;
+
+const USAGE: &str = "Usage:
+  iamlisp                      Start interactive session
+  iamlisp <file> [args...]     Run program from the file
+  iamlisp -e <expr> [args...]  Evaluate expression and print its result
+  iamlisp - [args...]          Run program from stdin
+  iamlisp -h, --help           Show this message
+
+Exit codes: 1 - runtime error, 2 - parse error, 64 - invalid usage,
+66 - input cannot be read.";
+
+#[derive(Debug, PartialEq)]
+enum Source {
+    File(String),
+    Expression(String),
+    Stdin,
+}
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Run { source: Source, args: Vec<String> },
+    Repl,
+    Help,
+}
+
+fn parse_args(args: &[String]) -> Result<Command, String> {
+    let (source, rest) = match args {
+        [] => return Ok(Command::Repl),
+        [flag, ..] if flag == "-h" || flag == "--help" => return Ok(Command::Help),
+        [flag, expression, rest @ ..] if flag == "-e" => {
+            (Source::Expression(expression.clone()), rest)
+        }
+        [flag] if flag == "-e" => return Err("Expression is expected after -e".to_string()),
+        [flag, rest @ ..] if flag == "-" => (Source::Stdin, rest),
+        [flag, ..] if flag.starts_with('-') => return Err(format!("Unknown option: {}", flag)),
+        [path, rest @ ..] => (Source::File(path.clone()), rest),
+    };
+
+    Ok(Command::Run {
+        source,
+        args: rest.to_vec(),
+    })
+}
+
+fn read_source(source: &Source) -> Result<(String, String), String> {
+    match source {
+        Source::File(path) => std::fs::read_to_string(path)
+            .map(|program| (path.clone(), program))
+            .map_err(|e| format!("error: Cannot read {}: {}", path, e)),
+        Source::Expression(expression) => Ok(("<expr>".to_string(), expression.clone())),
+        Source::Stdin => {
+            let mut program = String::new();
+
+            std::io::stdin()
+                .read_to_string(&mut program)
+                .map(|_| ("<stdin>".to_string(), program))
+                .map_err(|e| format!("error: Cannot read stdin: {}", e))
+        }
+    }
+}
+
+/// Runs the command line and returns the exit code.
+pub(crate) fn run(args: &[String]) -> i32 {
+    let (source, args) = match parse_args(args) {
+        Ok(Command::Run { source, args }) => (source, args),
+        // Without a terminal there is nobody to interact with, so the program
+        // is piped to stdin.
+        Ok(Command::Repl) if !std::io::stdin().is_terminal() => (Source::Stdin, vec![]),
+        Ok(Command::Repl) => {
+            return match repl::run() {
+                Ok(()) => 0,
+                Err(error) => {
+                    eprintln!("error: {}", error);
+                    EXIT_INPUT_ERROR
+                }
+            }
+        }
+        Ok(Command::Help) => {
+            println!("{}", USAGE);
+            return 0;
+        }
+        Err(error) => {
+            eprintln!("error: {}\n\n{}", error, USAGE);
+            return EXIT_USAGE_ERROR;
+        }
+    };
+
+    let (name, program) = match read_source(&source) {
+        Ok(input) => input,
+        Err(error) => {
+            eprintln!("{}", error);
+            return EXIT_INPUT_ERROR;
+        }
+    };
+
+    let mut interpreter = Interpreter::new();
+
+    interpreter.set_global(
+        "*args*",
+        args.into_iter()
+            .map(|arg| Value::String(arg).into())
+            .collect::<List<Expression>>(),
+    );
+
+    match interpreter.eval_source(&name, &program) {
+        Ok(result) => {
+            if matches!(source, Source::Expression(_)) && result != Value::Nil.into() {
+                println!("{}", result);
+            }
+
+            0
+        }
+        Err(error) => {
+            eprintln!("{}", error.render(&program));
+
+            match error {
+                Error::Input { .. } => EXIT_INPUT_ERROR,
+                Error::Parse { .. } => EXIT_PARSE_ERROR,
+                Error::Runtime { .. } => EXIT_RUNTIME_ERROR,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_command_line() {
+        let table = vec![
+            (
+                args(&["script.lisp", "a", "-e"]),
+                Ok(Command::Run {
+                    source: Source::File("script.lisp".to_string()),
+                    args: args(&["a", "-e"]),
+                }),
+            ),
+            (
+                args(&["-e", "(+ 1 2)", "a"]),
+                Ok(Command::Run {
+                    source: Source::Expression("(+ 1 2)".to_string()),
+                    args: args(&["a"]),
+                }),
+            ),
+            (args(&[]), Ok(Command::Repl)),
+            (
+                args(&["-", "a"]),
+                Ok(Command::Run {
+                    source: Source::Stdin,
+                    args: args(&["a"]),
+                }),
+            ),
+            (args(&["--help"]), Ok(Command::Help)),
+            (
+                args(&["-e"]),
+                Err("Expression is expected after -e".to_string()),
+            ),
+            (args(&["-x"]), Err("Unknown option: -x".to_string())),
+        ];
+
+        for (args, expected) in table {
+            assert_eq!(expected, parse_args(&args), "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn exit_codes() {
+        assert_eq!(0, run(&args(&["-e", "(+ 1 2)"])));
+        assert_eq!(0, run(&args(&["-e", "(nth *args* 1)", "a", "b"])));
+        assert_eq!(EXIT_RUNTIME_ERROR, run(&args(&["-e", "(foo)"])));
+        assert_eq!(EXIT_PARSE_ERROR, run(&args(&["-e", "(+ 1"])));
+        assert_eq!(EXIT_USAGE_ERROR, run(&args(&["-x"])));
+        assert_eq!(EXIT_INPUT_ERROR, run(&args(&["missing.lisp"])));
+    }
+}
@@ -1,13 +1,15 @@
-use std::fmt::{Display, Formatter};
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
 use std::mem::take;
+use std::ops::{Deref, DerefMut};
 
 #[macro_export]
 macro_rules! list {
     () => {{
-        $crate::data::List::<_>::new()
+        $crate::List::<_>::new()
     }};
     ($($args:expr),*) => {{
-        let mut list = $crate::data::List::new();
+        let mut list = $crate::List::new();
 
         $(
             #[allow(unused_assignments)]
@@ -20,57 +22,85 @@ macro_rules! list {
     }};
 }
 
-#[derive(Debug, PartialEq)]
-pub(crate) enum List<T> {
+pub enum List<T> {
     Empty,
-    Normal { car: T, cdr: Box<List<T>> },
+    Normal { car: T, cdr: Link<T> },
+}
+
+/// Pointer to the rest of the list. Lists are dropped in a loop over the
+/// links, as recursive drop of a long list overflows the stack.
+pub struct Link<T>(Box<List<T>>);
+
+impl<T> Deref for Link<T> {
+    type Target = List<T>;
+
+    fn deref(&self) -> &List<T> {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Link<T> {
+    fn deref_mut(&mut self) -> &mut List<T> {
+        &mut self.0
+    }
+}
+
+impl<T> Drop for Link<T> {
+    fn drop(&mut self) {
+        let mut next = take(&mut *self.0);
+
+        while let List::Normal { car: _, cdr } = &mut next {
+            // The unlinked item is dropped with an empty link.
+            next = take(&mut **cdr);
+        }
+    }
 }
 
 impl<T> List<T> {
-    pub(crate) fn new() -> Self {
+    pub fn new() -> Self {
         List::Empty
     }
 
-    pub(crate) fn cons(car: T, cdr: List<T>) -> Self {
+    pub fn cons(car: T, cdr: List<T>) -> Self {
         List::Normal {
             car,
-            cdr: Box::new(cdr),
+            cdr: Link(Box::new(cdr)),
         }
     }
 
-    pub(crate) fn head(&self) -> Option<&T> {
+    pub fn head(&self) -> Option<&T> {
         match self {
             List::Empty => None,
             List::Normal { car, cdr: _ } => Some(car),
         }
     }
 
-    pub(crate) fn head_mut(&mut self) -> Option<&mut T> {
+    pub fn head_mut(&mut self) -> Option<&mut T> {
         match self {
             List::Empty => None,
             List::Normal { car, cdr: _ } => Some(car),
         }
     }
 
-    pub(crate) fn tail(&self) -> &List<T> {
+    pub fn tail(&self) -> &List<T> {
         match self {
             List::Empty => self,
             List::Normal { car: _, cdr } => cdr,
         }
     }
 
-    pub(crate) fn tail_mut(&mut self) -> &mut List<T> {
+    pub fn tail_mut(&mut self) -> &mut List<T> {
         match self {
             List::Empty => self,
             List::Normal { car: _, cdr } => cdr,
         }
     }
 
-    pub(crate) fn is_empty(&self) -> bool {
+    pub fn is_empty(&self) -> bool {
         matches!(self, List::Empty)
     }
 
-    pub(crate) fn len(&self) -> i64 {
+    pub fn len(&self) -> i64 {
         let mut len = 0;
         let mut cursor = self;
 
@@ -82,88 +112,95 @@ impl<T> List<T> {
         len
     }
 
-    pub(crate) fn push(&mut self, item: T) -> &mut Self {
-        match self {
-            List::Empty => self.push_top(item),
-            List::Normal { car: _, cdr } => cdr.push(item),
+    pub fn push(&mut self, item: T) -> &mut Self {
+        let mut cursor = &mut *self;
+
+        while let List::Normal { car: _, cdr } = cursor {
+            cursor = cdr;
         }
+
+        cursor.push_top(item);
+
+        self
     }
 
-    pub(crate) fn push_top(&mut self, item: T) -> &mut Self {
+    pub fn push_top(&mut self, item: T) -> &mut Self {
         *self = Self::cons(item, take(self));
 
         self
     }
 
-    pub(crate) fn reverse(self) -> List<T> {
+    pub fn reverse(self) -> List<T> {
         let mut acc = List::new();
         let mut current = self;
 
-        while let List::Normal { car, cdr } = current {
+        while let List::Normal { car, mut cdr } = current {
             acc.push_top(car);
-            current = *cdr;
+            current = take(&mut *cdr);
         }
 
         acc
     }
 
-    pub(crate) fn map<CB, R: Display>(self, cb: CB) -> List<R>
+    pub fn map<CB, R: Display>(self, cb: CB) -> List<R>
     where
         CB: Fn(T) -> R,
     {
         let mut acc = List::new();
         let mut current = self.reverse();
 
-        while let List::Normal { car, cdr } = current {
+        while let List::Normal { car, mut cdr } = current {
             acc.push_top(cb(car));
-            current = *cdr;
+            current = take(&mut *cdr);
         }
 
         acc
     }
 
-    pub(crate) fn filter<CB>(self, cb: CB) -> List<T>
+    pub fn filter<CB>(self, cb: CB) -> List<T>
     where
         CB: Fn(&T) -> bool,
     {
         let mut acc = List::new();
         let mut current = self.reverse();
 
-        while let List::Normal { car, cdr } = current {
+        while let List::Normal { car, mut cdr } = current {
             if cb(&car) {
                 acc.push_top(car);
             }
 
-            current = *cdr;
+            current = take(&mut *cdr);
         }
 
         acc
     }
 
-    pub(crate) fn shift(&mut self) -> Option<T> {
+    pub fn shift(&mut self) -> Option<T> {
         match take(self) {
             List::Empty => None,
-            List::Normal { car, cdr } => {
-                *self = *cdr;
+            List::Normal { car, mut cdr } => {
+                *self = take(&mut *cdr);
                 Some(car)
             }
         }
     }
 
-    pub(crate) fn pop(&mut self) -> Option<T> {
-        if self.tail().is_empty() {
-            self.shift()
-        } else {
-            self.tail_mut().pop()
+    pub fn pop(&mut self) -> Option<T> {
+        let mut cursor = self;
+
+        while !cursor.tail().is_empty() {
+            cursor = cursor.tail_mut();
         }
+
+        cursor.shift()
     }
 
-    pub(crate) fn iter(&self) -> ListRefIter<T> {
-        ListRefIter { next: &self }
+    pub fn iter(&self) -> ListRefIter<'_, T> {
+        ListRefIter { next: self }
     }
 }
 
-pub(crate) struct ListRefIter<'a, T> {
+pub struct ListRefIter<'a, T> {
     next: &'a List<T>,
 }
 
@@ -186,16 +223,16 @@ impl<T: Display> Display for List<T> {
         let mut first = true;
         let mut current = self;
 
-        str.push_str("(");
+        str.push('(');
         while let List::Normal { car, cdr } = current {
             if !first {
-                str.push_str(" ");
+                str.push(' ');
             }
             str.push_str(&format!("{}", car));
             current = cdr;
             first = false
         }
-        str.push_str(")");
+        str.push(')');
 
         write!(f, "{}", str)
     }
@@ -205,9 +242,9 @@ impl<T> FromIterator<T> for List<T> {
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
         let mut list = List::new();
         for item in iter {
-            list.push(item);
+            list.push_top(item);
         }
-        list
+        list.reverse()
     }
 }
 
@@ -220,7 +257,7 @@ impl<T> IntoIterator for List<T> {
     }
 }
 
-pub(crate) struct ListIter<T> {
+pub struct ListIter<T> {
     list: Option<List<T>>,
 }
 
@@ -229,8 +266,8 @@ impl<T> Iterator for ListIter<T> {
 
     fn next(&mut self) -> Option<Self::Item> {
         match self.list.take() {
-            Some(List::Normal { car, cdr }) => {
-                self.list = Some(*cdr);
+            Some(List::Normal { car, mut cdr }) => {
+                self.list = Some(take(&mut *cdr));
                 Some(car)
             }
             _ => None,
@@ -238,6 +275,16 @@ impl<T> Iterator for ListIter<T> {
     }
 }
 
+impl<T: Hash> Hash for List<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+
+        for item in self.iter() {
+            item.hash(state);
+        }
+    }
+}
+
 impl<T> Default for List<T> {
     fn default() -> Self {
         List::new()
@@ -246,13 +293,19 @@ impl<T> Default for List<T> {
 
 impl<T: Clone> Clone for List<T> {
     fn clone(&self) -> List<T> {
-        match self {
-            List::Empty => List::new(),
-            List::Normal { car, cdr } => List::Normal {
-                car: car.clone(),
-                cdr: cdr.clone(),
-            },
-        }
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &List<T>) -> bool {
+        self.len() == other.len() && self.iter().zip(other.iter()).all(|(a, b)| a == b)
+    }
+}
+
+impl<T: Debug> Debug for List<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
     }
 }
 
@@ -321,4 +374,16 @@ mod tests {
 
         assert_eq!("(1 2 3)", list.to_string());
     }
+
+    #[test]
+    fn test_long_list() {
+        let list: List<_> = (0..1_000_000).collect();
+        let copy = list.clone();
+
+        assert_eq!(list, copy);
+        assert_eq!(1_000_000, copy.len());
+
+        drop(list);
+        drop(copy);
+    }
 }
@@ -1,3 +1,12 @@
 mod list;
 
-pub(crate) use list::List;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::BuildHasherDefault;
+
+pub use im_rc::Vector;
+pub use list::List;
+
+/// Persistent hash map and set with deterministic hashing, so collections with
+/// the same items are always printed in the same order.
+pub type HashMap<K, V> = im_rc::HashMap<K, V, BuildHasherDefault<DefaultHasher>>;
+pub type HashSet<T> = im_rc::HashSet<T, BuildHasherDefault<DefaultHasher>>;
@@ -0,0 +1,385 @@
+use crate::data::{List, Vector};
+use crate::eval::env::Env;
+use crate::eval::native_calls::math::Number;
+use crate::eval::native_calls::Op;
+use crate::eval::types::{Expression, Value};
+use anyhow::bail;
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+use std::marker::PhantomData;
+
+/// Conversion of an evaluated argument to a Rust value.
+pub trait FromValue: Sized {
+    /// Name of the expected type, used in errors.
+    fn type_name() -> String;
+
+    fn from_value(expression: &Expression) -> Option<Self>;
+}
+
+/// Conversion of a Rust value to the result of a native call.
+pub trait IntoValue {
+    fn into_value(self) -> Expression;
+}
+
+impl FromValue for Expression {
+    fn type_name() -> String {
+        "Expression".to_string()
+    }
+
+    fn from_value(expression: &Expression) -> Option<Self> {
+        Some(expression.clone())
+    }
+}
+
+impl IntoValue for Expression {
+    fn into_value(self) -> Expression {
+        self
+    }
+}
+
+impl IntoValue for Value {
+    fn into_value(self) -> Expression {
+        self.into()
+    }
+}
+
+impl IntoValue for () {
+    fn into_value(self) -> Expression {
+        Expression::default()
+    }
+}
+
+impl FromValue for i64 {
+    fn type_name() -> String {
+        "Int64".to_string()
+    }
+
+    fn from_value(expression: &Expression) -> Option<Self> {
+        match expression {
+            Expression::Value(Value::Int64(int)) => Some(*int),
+            _ => None,
+        }
+    }
+}
+
+impl IntoValue for i64 {
+    fn into_value(self) -> Expression {
+        Value::Int64(self).into()
+    }
+}
+
+// Integers and ratios are accepted where floats are expected, like in math operations.
+impl FromValue for f64 {
+    fn type_name() -> String {
+        "Float64".to_string()
+    }
+
+    fn from_value(expression: &Expression) -> Option<Self> {
+        match expression {
+            Expression::Value(Value::Float64(float)) => Some(*float),
+            Expression::Value(Value::Int64(int)) => Some(*int as f64),
+            Expression::Value(Value::BigInt(big)) => big.to_f64(),
+            Expression::Value(Value::Ratio(ratio)) => ratio.to_f64(),
+            _ => None,
+        }
+    }
+}
+
+impl IntoValue for f64 {
+    fn into_value(self) -> Expression {
+        Value::Float64(self).into()
+    }
+}
+
+/// Integers of any size.
+impl FromValue for BigInt {
+    fn type_name() -> String {
+        "BigInt".to_string()
+    }
+
+    fn from_value(expression: &Expression) -> Option<Self> {
+        match expression {
+            Expression::Value(Value::Int64(int)) => Some(BigInt::from(*int)),
+            Expression::Value(Value::BigInt(big)) => Some(big.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Integers that fit are returned as `Int64`.
+impl IntoValue for BigInt {
+    fn into_value(self) -> Expression {
+        Number::integer(self).into()
+    }
+}
+
+impl FromValue for bool {
+    fn type_name() -> String {
+        "Bool".to_string()
+    }
+
+    fn from_value(expression: &Expression) -> Option<Self> {
+        match expression {
+            Expression::Value(Value::Bool(bool)) => Some(*bool),
+            _ => None,
+        }
+    }
+}
+
+impl IntoValue for bool {
+    fn into_value(self) -> Expression {
+        Value::Bool(self).into()
+    }
+}
+
+impl FromValue for String {
+    fn type_name() -> String {
+        "String".to_string()
+    }
+
+    fn from_value(expression: &Expression) -> Option<Self> {
+        match expression {
+            Expression::Value(Value::String(string)) => Some(string.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl IntoValue for String {
+    fn into_value(self) -> Expression {
+        Value::String(self).into()
+    }
+}
+
+impl IntoValue for &str {
+    fn into_value(self) -> Expression {
+        Value::String(self.to_string()).into()
+    }
+}
+
+/// `Nil` is `None`.
+impl<T: FromValue> FromValue for Option<T> {
+    fn type_name() -> String {
+        format!("{} or Nil", T::type_name())
+    }
+
+    fn from_value(expression: &Expression) -> Option<Self> {
+        match expression {
+            Expression::Value(Value::Nil) => Some(None),
+            expression => T::from_value(expression).map(Some),
+        }
+    }
+}
+
+impl<T: IntoValue> IntoValue for Option<T> {
+    fn into_value(self) -> Expression {
+        self.map_or_else(Expression::default, IntoValue::into_value)
+    }
+}
+
+/// Vectors and lists are read, vectors are returned.
+impl<T: FromValue> FromValue for Vec<T> {
+    fn type_name() -> String {
+        format!("Vector of {}", T::type_name())
+    }
+
+    fn from_value(expression: &Expression) -> Option<Self> {
+        match expression {
+            Expression::Value(Value::Vector(items)) => items.iter().map(T::from_value).collect(),
+            Expression::List(list) => list.iter().map(T::from_value).collect(),
+            _ => None,
+        }
+    }
+}
+
+impl<T: IntoValue> IntoValue for Vec<T> {
+    fn into_value(self) -> Expression {
+        Value::Vector(self.into_iter().map(IntoValue::into_value).collect()).into()
+    }
+}
+
+/// Tuples are vectors of the same length, like map entries `[key value]`.
+macro_rules! impl_tuple {
+    ($len:literal, $($item:ident),+) => {
+        impl<$($item: FromValue),+> FromValue for ($($item,)+) {
+            fn type_name() -> String {
+                format!("[{}]", [$($item::type_name()),+].join(" "))
+            }
+
+            fn from_value(expression: &Expression) -> Option<Self> {
+                let items = match expression {
+                    Expression::Value(Value::Vector(items)) if items.len() == $len => items,
+                    _ => return None,
+                };
+                let mut items = items.iter();
+
+                Some(($($item::from_value(items.next()?)?,)+))
+            }
+        }
+
+        impl<$($item: IntoValue),+> IntoValue for ($($item,)+) {
+            #[allow(non_snake_case)]
+            fn into_value(self) -> Expression {
+                let ($($item,)+) = self;
+
+                Value::Vector(Vector::from_iter([$($item.into_value()),+])).into()
+            }
+        }
+    };
+}
+
+impl_tuple!(1, A);
+impl_tuple!(2, A, B);
+impl_tuple!(3, A, B, C);
+impl_tuple!(4, A, B, C, D);
+
+fn argument<T: FromValue>(position: usize, arg: &Expression) -> anyhow::Result<T> {
+    match T::from_value(arg) {
+        Some(value) => Ok(value),
+        None => bail!(
+            "expected {} at argument {}, got {}",
+            T::type_name(),
+            position,
+            arg.type_name()
+        ),
+    }
+}
+
+/// Rust function with arguments and result converted from and to values.
+/// `Args` is the tuple of argument types, so functions of different arity
+/// don't clash.
+pub trait TypedFn<Args> {
+    fn call(&self, args: &List<Expression>) -> anyhow::Result<Expression>;
+}
+
+macro_rules! impl_typed_fn {
+    ($($arg:ident),*) => {
+        impl<Func, Ret, $($arg: FromValue),*> TypedFn<($($arg,)*)> for Func
+        where
+            Func: Fn($($arg),*) -> anyhow::Result<Ret>,
+            Ret: IntoValue,
+        {
+            #[allow(non_snake_case, unused_mut, unused_variables)]
+            fn call(&self, args: &List<Expression>) -> anyhow::Result<Expression> {
+                let arity = <[&str]>::len(&[$(stringify!($arg)),*]);
+
+                if args.len() != arity as i64 {
+                    bail!(
+                        "expected {} argument{}, got {}",
+                        arity,
+                        if arity == 1 { "" } else { "s" },
+                        args.len()
+                    );
+                }
+
+                let mut args = args.iter().enumerate();
+
+                $(
+                    let (index, arg) = args.next().unwrap();
+                    let $arg = argument::<$arg>(index + 1, arg)?;
+                )*
+
+                self($($arg),*).map(IntoValue::into_value)
+            }
+        }
+    };
+}
+
+impl_typed_fn!();
+impl_typed_fn!(A);
+impl_typed_fn!(A, B);
+impl_typed_fn!(A, B, C);
+impl_typed_fn!(A, B, C, D);
+impl_typed_fn!(A, B, C, D, E);
+impl_typed_fn!(A, B, C, D, E, F);
+
+/// Native call made of a typed Rust function.
+pub(crate) struct TypedOp<Func, Args> {
+    name: &'static str,
+    func: Func,
+    args: PhantomData<fn(Args)>,
+}
+
+impl<Func, Args> TypedOp<Func, Args> {
+    pub(crate) fn new(name: &'static str, func: Func) -> Self {
+        Self {
+            name,
+            func,
+            args: PhantomData,
+        }
+    }
+}
+
+impl<Func: TypedFn<Args>, Args> Op for TypedOp<Func, Args> {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        self.func.call(args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::list;
+
+    fn int(int: i64) -> Expression {
+        Value::Int64(int).into()
+    }
+
+    fn string(string: &str) -> Expression {
+        Value::String(string.to_string()).into()
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(Some(1), i64::from_value(&int(1)));
+        assert_eq!(Some(1.0), f64::from_value(&int(1)));
+        assert_eq!(None, i64::from_value(&string("1")));
+        assert_eq!(
+            Some(None),
+            Option::<i64>::from_value(&Expression::default())
+        );
+        assert_eq!(
+            Some(vec![1, 2]),
+            Vec::<i64>::from_value(&list![int(1), int(2)].into())
+        );
+        assert_eq!(
+            None,
+            Vec::<i64>::from_value(&vec![int(1), string("2")].into_value())
+        );
+        assert_eq!(
+            Some((1, "a".to_string())),
+            <(i64, String)>::from_value(&(1, "a").into_value())
+        );
+        assert_eq!(None, <(i64, i64)>::from_value(&vec![1].into_value()));
+        assert_eq!(Expression::default(), None::<i64>.into_value());
+        assert_eq!("[Int64 String]", <(i64, String)>::type_name());
+    }
+
+    #[test]
+    fn typed_fn_checks_arguments() {
+        let env = Env::new();
+        let repeat = TypedOp::new("repeat", |count: i64, text: String| {
+            Ok(text.repeat(count as usize))
+        });
+
+        assert_eq!(
+            string("abab"),
+            repeat.apply(&list![int(2), string("ab")], &env).unwrap()
+        );
+        assert_eq!(
+            "expected String at argument 2, got Int64",
+            repeat
+                .apply(&list![int(2), int(3)], &env)
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!(
+            "expected 2 arguments, got 1",
+            repeat.apply(&list![int(2)], &env).unwrap_err().to_string()
+        );
+    }
+}
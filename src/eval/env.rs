@@ -3,34 +3,64 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
 
+/// Bindings of names to values, with the bindings of the parent visible.
 #[derive(Debug, Clone)]
-pub(crate) struct Env {
+pub struct Env {
     values: Rc<RefCell<HashMap<&'static str, Expression>>>,
     parent: Option<Box<Env>>,
 }
 
 impl Env {
     pub(crate) fn new() -> Self {
-        let env = Self {
+        Self {
             values: Rc::default(),
             parent: None,
-        };
-
-        env
+        }
     }
 
-    pub(crate) fn get(&self, name: &'static str) -> Option<Expression> {
+    pub(crate) fn get(&self, name: &str) -> Option<Expression> {
         self.values
             .borrow()
             .get(name)
             .cloned()
-            .or_else(|| self.parent.as_ref().map(|e| e.get(name)).flatten())
+            .or_else(|| self.parent.as_ref().and_then(|e| e.get(name)))
     }
 
     pub(crate) fn set(&mut self, name: &'static str, value: Expression) {
         self.values.borrow_mut().insert(name, value);
     }
 
+    /// Bindings of this environment, without the ones of its parents.
+    #[cfg(feature = "cli")]
+    pub(crate) fn bindings(&self) -> Vec<(&'static str, Expression)> {
+        self.values
+            .borrow()
+            .iter()
+            .map(|(name, value)| (*name, value.clone()))
+            .collect()
+    }
+
+    #[cfg(feature = "cli")]
+    pub(crate) fn parent(&self) -> Option<&Env> {
+        self.parent.as_deref()
+    }
+
+    /// Names bound in this environment and its parents.
+    #[cfg(feature = "cli")]
+    pub(crate) fn names(&self) -> Vec<&'static str> {
+        let mut names = self.values.borrow().keys().copied().collect::<Vec<_>>();
+
+        if let Some(parent) = &self.parent {
+            names.extend(parent.names());
+        }
+
+        names
+    }
+
+    pub(crate) fn is_same(&self, other: &Env) -> bool {
+        Rc::ptr_eq(&self.values, &other.values)
+    }
+
     pub(crate) fn child(&self) -> Env {
         Env {
             values: Rc::default(),
@@ -43,8 +73,4 @@ impl PartialEq for Env {
     fn eq(&self, _other: &Self) -> bool {
         true
     }
-
-    fn ne(&self, _other: &Self) -> bool {
-        false
-    }
 }
@@ -0,0 +1,61 @@
+use crate::eval::types::{Expression, Value};
+use crate::read::FormId;
+use std::fmt::{Display, Formatter};
+
+/// Kind of errors raised by native calls and the evaluator.
+pub(crate) const DEFAULT_ERROR_KIND: &str = "error";
+
+/// Error raised by a native call or the evaluator, as seen by `catch`.
+pub(crate) fn error_value(error: &anyhow::Error) -> Expression {
+    Value::Error {
+        kind: DEFAULT_ERROR_KIND.to_string(),
+        message: error.to_string(),
+        data: Box::default(),
+    }
+    .into()
+}
+
+/// Message of the thrown value, reported if nothing catches it.
+pub(crate) fn error_message(value: &Expression) -> String {
+    match value {
+        Expression::Value(Value::Error { message, .. }) => message.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// Error of evaluation along with the forms that were being evaluated when it
+/// happened, innermost first, so it can be located in the source.
+#[derive(Debug)]
+pub(crate) struct EvalError {
+    pub(crate) forms: Vec<FormId>,
+    pub(crate) error: anyhow::Error,
+}
+
+impl EvalError {
+    /// Attaches forms to the error. Forms attached by a nested evaluation are
+    /// more specific, so they stay first.
+    pub(crate) fn locate(
+        error: anyhow::Error,
+        forms: impl IntoIterator<Item = FormId>,
+    ) -> anyhow::Error {
+        match error.downcast::<EvalError>() {
+            Ok(mut located) => {
+                located.forms.extend(forms);
+                located.into()
+            }
+            Err(error) => EvalError {
+                forms: forms.into_iter().collect(),
+                error,
+            }
+            .into(),
+        }
+    }
+}
+
+impl Display for EvalError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.error)
+    }
+}
+
+impl std::error::Error for EvalError {}
@@ -1,26 +1,79 @@
-use crate::data::List;
+use crate::data::{HashMap, List};
 use crate::eval::env::Env;
+use crate::eval::error::{error_message, error_value, EvalError};
 use crate::eval::forms::{
-    iamlisp_eval_cond_expression, iamlisp_eval_quote_expression, iamlisp_is_cond_expression,
-    iamlisp_is_quote_expression,
+    iamlisp_eval_cond_expression, iamlisp_eval_eval_expression, iamlisp_eval_loop_expression,
+    iamlisp_eval_quasiquote_expression, iamlisp_eval_quote_expression,
+    iamlisp_eval_recur_expression, iamlisp_eval_try_expression, iamlisp_eval_try_frame,
+    iamlisp_is_cond_expression, iamlisp_is_eval_expression, iamlisp_is_loop_expression,
+    iamlisp_is_quasiquote_expression, iamlisp_is_quote_expression, iamlisp_is_recur_expression,
+    iamlisp_is_try_expression, iamlisp_is_try_frame, iamlisp_unwind, TryFrame,
 };
-use crate::eval::types::{Expression, Value};
-use crate::{begin_symbol, def_symbol, list, quote_symbol};
-use anyhow::bail;
+use crate::eval::native_calls::{
+    Continuation, MapConstructor, NativeResult, Op, SetConstructor, VectorConstructor,
+};
+use crate::eval::types::{Expression, NativeCall, Value};
+use crate::read::FormId;
+use crate::{begin_symbol, def_symbol, list};
+use anyhow::{anyhow, bail};
+use std::cell::RefCell;
+use std::mem::take;
+use std::rc::Rc;
+use std::sync::Arc;
 
 pub(crate) struct StackEntry {
     pub(crate) input: List<Expression>,
     pub(crate) output: List<Expression>,
     pub(crate) env: Env,
+    /// Form this entry evaluates, used to locate errors in the source.
+    pub(crate) form: Option<Rc<List<Expression>>>,
+    /// Set on the frame of `try`, which gets the value of its body.
+    pub(crate) try_frame: Option<TryFrame>,
+}
+
+/// Stack of entries waiting for evaluation, the top entry is evaluated next.
+#[derive(Default)]
+pub(crate) struct CallStack {
+    entries: Vec<StackEntry>,
+    max_depth: usize,
+}
+
+impl CallStack {
+    pub(crate) fn push_top(&mut self, entry: StackEntry) {
+        self.entries.push(entry);
+        self.max_depth = self.max_depth.max(self.entries.len());
+    }
+
+    pub(crate) fn shift(&mut self) -> Option<StackEntry> {
+        self.entries.pop()
+    }
+
+    pub(crate) fn head_mut(&mut self) -> Option<&mut StackEntry> {
+        self.entries.last_mut()
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct EvalStats {
+    pub(crate) max_stack_depth: usize,
+    /// Number of stack entries taken for evaluation.
+    pub(crate) steps: usize,
 }
 
-pub(crate) type CallStack = List<StackEntry>;
+impl EvalStats {
+    pub(crate) fn merge(self, other: EvalStats) -> EvalStats {
+        EvalStats {
+            max_stack_depth: self.max_stack_depth.max(other.max_stack_depth),
+            steps: self.steps + other.steps,
+        }
+    }
+}
 
 fn assign_env_values(env: &mut Env, symbol: Expression, value: Expression) -> anyhow::Result<()> {
     match (symbol, value) {
         (Expression::List(symbols_list), Expression::List(values_list)) => {
-            let mut symbols_iter = symbols_list.into_iter();
-            let mut values_list = *values_list;
+            let mut symbols_iter = symbols_list.iter().cloned();
+            let mut values_list = Rc::unwrap_or_clone(values_list);
 
             let mut after_dot = false;
 
@@ -49,7 +102,7 @@ fn assign_env_values(env: &mut Env, symbol: Expression, value: Expression) -> an
                 }
             }
         }
-        (Expression::List(symbols_list), value) => {
+        (Expression::List(symbols_list), _) => {
             bail!(
                 "Unable to destruct non-list to symbols list: {}",
                 symbols_list
@@ -105,11 +158,12 @@ fn iamlisp_is_variables_definition(stack_entry: &StackEntry) -> bool {
 fn iamlisp_eval_variables_definition(
     mut stack_entry: StackEntry,
     stack: &mut CallStack,
+    return_value: &mut Expression,
 ) -> anyhow::Result<()> {
     let output_vec = stack_entry.output.iter().collect::<Vec<_>>();
 
     match output_vec.as_slice() {
-        &[] => match stack_entry.input.shift() {
+        [] => match stack_entry.input.shift() {
             Some(def_symbol!()) => {
                 stack_entry.output.push(def_symbol!());
             }
@@ -120,8 +174,8 @@ fn iamlisp_eval_variables_definition(
                 );
             }
         },
-        &[def_symbol!(), symbol, value] => {
-            assign_env_values(&mut stack_entry.env, symbol.clone(), value.clone())?;
+        [def_symbol!(), symbol, value] => {
+            assign_env_values(&mut stack_entry.env, (*symbol).clone(), (*value).clone())?;
             stack_entry.output = list![def_symbol!()];
         }
         _ => bail!(
@@ -134,12 +188,11 @@ fn iamlisp_eval_variables_definition(
         (Some(symbol), Some(expr)) => {
             stack_entry.output.push(symbol);
 
-            iamlisp_eval_next_input_expression(&expr, stack_entry, stack)?;
+            iamlisp_eval_next_input_expression(&expr, stack_entry, stack)
         }
-        _ => (),
+        (None, _) => iamlisp_pass_value_to_next_stack_entry(Value::Nil.into(), stack, return_value),
+        (Some(symbol), None) => bail!("Value is expected for definition of {}", symbol),
     }
-
-    Ok(())
 }
 
 /*
@@ -186,7 +239,8 @@ fn iamlisp_eval_lambda_definition(
     let lambda = Value::Lambda {
         env: stack_entry.env.clone(),
         args: lambda_args,
-        body: Box::from(stack_entry.input.clone()),
+        body: Rc::new(stack_entry.input.clone()),
+        doc: None,
     }
     .into();
 
@@ -220,7 +274,8 @@ fn iamlisp_eval_macro_definition(
 
     let r#macro = Value::Macro {
         args: lambda_args,
-        body: Box::from(stack_entry.input.clone()),
+        body: Rc::new(stack_entry.input.clone()),
+        doc: None,
     }
     .into();
 
@@ -229,6 +284,211 @@ fn iamlisp_eval_macro_definition(
     Ok(())
 }
 
+fn iamlisp_is_named_definition(stack_entry: &StackEntry) -> bool {
+    matches!(
+        stack_entry.input.head(),
+        Some(Expression::Symbol("defun" | "defmacro"))
+    )
+}
+
+/*
+ Function or macro definition:
+
+ (defun sum (a b) "Sums two numbers" (+ a b))  =>  {sum: (lambda (a b) "Sums two numbers" (+ a b))}
+*/
+fn iamlisp_eval_named_definition(
+    mut stack_entry: StackEntry,
+    stack: &mut CallStack,
+    return_value: &mut Expression,
+) -> anyhow::Result<()> {
+    let kind = match stack_entry.input.shift() {
+        Some(Expression::Symbol(kind @ ("defun" | "defmacro"))) => kind,
+        _ => {
+            bail!("Invalid definition expression");
+        }
+    };
+
+    let name = match stack_entry.input.shift() {
+        Some(Expression::Symbol(name)) => name,
+        Some(expression) => {
+            bail!("{} - is not valid {} name", expression, kind);
+        }
+        None => {
+            bail!("Name is expected in {} expression", kind);
+        }
+    };
+
+    let args = match stack_entry.input.shift() {
+        Some(Expression::List(args)) => args,
+        _ => {
+            bail!("Invalid {} arguments", kind);
+        }
+    };
+
+    // A string is a docstring only if something else follows it, otherwise
+    // it is the return value of the body.
+    let doc = match stack_entry.input.head() {
+        Some(Expression::Value(Value::String(doc))) if stack_entry.input.len() > 1 => {
+            let doc = doc.clone();
+            let _ = stack_entry.input.shift();
+            Some(doc)
+        }
+        _ => None,
+    };
+
+    let body = Rc::new(take(&mut stack_entry.input));
+
+    let value = match kind {
+        "defun" => Value::Lambda {
+            env: stack_entry.env.clone(),
+            args,
+            body,
+            doc,
+        },
+        _ => Value::Macro { args, body, doc },
+    };
+
+    stack_entry.env.set(name, value.into());
+
+    iamlisp_pass_value_to_next_stack_entry(Value::Nil.into(), stack, return_value)
+}
+
+fn iamlisp_is_macro_call(stack_entry: &StackEntry) -> bool {
+    matches!(
+        stack_entry.output.head(),
+        Some(Expression::Value(Value::Macro { .. }))
+    )
+}
+
+/*
+ Macro call:
+
+ [backwards]                                ((def a 1) (def a 2))         {}
+
+ []                                         (begin (cons ...))            {body: ((def a 1) (def a 2))}
+ [backwards]                                ()                            {}
+
+ [backwards (begin (def a 2) (def a 1))]    ()                            {}
+
+ []                                         (begin (def a 2) (def a 1))   {}
+*/
+fn iamlisp_eval_macro_call(
+    mut stack_entry: StackEntry,
+    stack: &mut CallStack,
+    return_value: &mut Expression,
+) -> anyhow::Result<()> {
+    let output_vec = stack_entry.output.iter().collect::<Vec<_>>();
+
+    match output_vec.as_slice() {
+        [Expression::Value(Value::Macro { args, body, .. })] => {
+            let mut env = stack_entry.env.child();
+
+            let args_names = List::clone(args);
+            let args_forms = take(&mut stack_entry.input);
+
+            assign_env_values(&mut env, args_names.into(), args_forms.into())?;
+
+            let mut body = List::clone(body);
+
+            body.push_top(begin_symbol!());
+
+            stack.push_top(stack_entry);
+            stack.push_top(StackEntry {
+                env,
+                input: body,
+                output: list![],
+                form: None,
+                try_frame: None,
+            });
+
+            Ok(())
+        }
+        [_, expansion] => {
+            let expansion = (*expansion).clone();
+
+            iamlisp_eval_expression_in_place(expansion, stack_entry.env, stack, return_value)
+        }
+        _ => bail!("Unexpected macro call output state: {}", stack_entry.output),
+    }
+}
+
+/// Expands macro call once. Returns `None` if the form is not a macro call.
+pub(crate) fn iamlisp_macroexpand_1(
+    form: &Expression,
+    env: &Env,
+) -> anyhow::Result<Option<Expression>> {
+    let list = match form {
+        Expression::List(list) => list,
+        _ => return Ok(None),
+    };
+
+    let (args, body) = match list.head() {
+        Some(Expression::Value(Value::Macro { args, body, .. })) => (args.clone(), body.clone()),
+        Some(Expression::Symbol(name)) => match env.get(name) {
+            Some(Expression::Value(Value::Macro { args, body, .. })) => (args, body),
+            _ => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+
+    let mut macro_env = env.child();
+
+    assign_env_values(
+        &mut macro_env,
+        List::clone(&args).into(),
+        list.tail().clone().into(),
+    )?;
+
+    let mut body = List::clone(&body);
+
+    body.push_top(begin_symbol!());
+
+    Ok(Some(iamlisp_eval_list(&body, &macro_env)?))
+}
+
+/// Evaluates expression in place of the current stack entry, so its result goes
+/// directly to the entry that is waiting for the current one.
+pub(crate) fn iamlisp_eval_expression_in_place(
+    expression: Expression,
+    env: Env,
+    stack: &mut CallStack,
+    return_value: &mut Expression,
+) -> anyhow::Result<()> {
+    match expression {
+        Expression::List(list) => {
+            stack.push_top(StackEntry {
+                env,
+                input: List::clone(&list),
+                output: list![],
+                form: Some(list),
+                try_frame: None,
+            });
+
+            Ok(())
+        }
+        Expression::Symbol(name) => {
+            iamlisp_pass_value_to_next_stack_entry(get_from_env(name, &env)?, stack, return_value)
+        }
+        Expression::Value(value) => match iamlisp_literal_constructor(&value) {
+            Some(input) => {
+                stack.push_top(StackEntry {
+                    env,
+                    input,
+                    output: list![],
+                    form: None,
+                    try_frame: None,
+                });
+
+                Ok(())
+            }
+            None => iamlisp_pass_value_to_next_stack_entry(value.into(), stack, return_value),
+        },
+        expression => {
+            bail!("Invalid expression: {}", expression);
+        }
+    }
+}
+
 pub(crate) fn iamlisp_eval_next_input_expression(
     expression: &Expression,
     mut current_stack_entry: StackEntry,
@@ -242,15 +502,32 @@ pub(crate) fn iamlisp_eval_next_input_expression(
 
             stack.push_top(StackEntry {
                 env,
-                input: *list.clone(),
+                input: List::clone(list),
                 output: list![],
+                form: Some(list.clone()),
+                try_frame: None,
             });
         }
-        Expression::Value(value) => {
-            current_stack_entry.output.push(value.clone().into());
+        Expression::Value(value) => match iamlisp_literal_constructor(value) {
+            Some(input) => {
+                let env = current_stack_entry.env.clone();
+
+                stack.push_top(current_stack_entry);
+
+                stack.push_top(StackEntry {
+                    env,
+                    input,
+                    output: list![],
+                    form: None,
+                    try_frame: None,
+                });
+            }
+            None => {
+                current_stack_entry.output.push(value.clone().into());
 
-            stack.push_top(current_stack_entry);
-        }
+                stack.push_top(current_stack_entry);
+            }
+        },
         Expression::Symbol(name) => {
             current_stack_entry
                 .output
@@ -266,6 +543,134 @@ pub(crate) fn iamlisp_eval_next_input_expression(
     Ok(())
 }
 
+/// Native call waiting for the result of its callback. It takes the place of
+/// the native call on the stack and gets the result as its only argument.
+struct ContinuationCall {
+    name: &'static str,
+    then: RefCell<Option<Continuation>>,
+}
+
+impl Op for ContinuationCall {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn apply(&self, args: &List<Expression>, env: &Env) -> anyhow::Result<Expression> {
+        iamlisp_eval_native_result(self.apply_with_callbacks(args, env)?, env)
+    }
+
+    fn apply_with_callbacks(
+        &self,
+        args: &List<Expression>,
+        _env: &Env,
+    ) -> anyhow::Result<NativeResult> {
+        let then = self
+            .then
+            .borrow_mut()
+            .take()
+            .ok_or_else(|| anyhow!("Continuation of {} is already called", self.name))?;
+
+        then(args.head().cloned().unwrap_or_default())
+    }
+}
+
+fn iamlisp_is_callable(expression: &Expression) -> bool {
+    matches!(
+        expression,
+        Expression::Value(Value::Lambda { .. } | Value::NativeCall(_))
+    )
+}
+
+/*
+ Native call with callback:
+
+ [apply f (1 2)]                ()      {}
+
+ [<apply>]                      ()      {}
+ [f 1 2]                        ()
+
+ [<apply> 3]                    ()      {}
+
+ 3
+*/
+// Expressions are bound to one thread anyway, as they are shared with `Rc`.
+#[allow(clippy::arc_with_non_send_sync)]
+pub(crate) fn iamlisp_push_callback(
+    name: &'static str,
+    callable: Expression,
+    args: List<Expression>,
+    then: Continuation,
+    current_stack_entry: StackEntry,
+    call_stack: &mut CallStack,
+) -> anyhow::Result<()> {
+    if !iamlisp_is_callable(&callable) {
+        bail!(
+            "Expression is not callable type: {} (args: {})",
+            callable,
+            args
+        );
+    }
+
+    let continuation = ContinuationCall {
+        name,
+        then: RefCell::new(Some(then)),
+    };
+
+    call_stack.push_top(StackEntry {
+        env: current_stack_entry.env.clone(),
+        input: list![],
+        output: list![NativeCall(Arc::new(Box::new(continuation))).into()],
+        form: current_stack_entry.form.clone(),
+        try_frame: None,
+    });
+    call_stack.push_top(StackEntry {
+        env: current_stack_entry.env,
+        input: list![],
+        output: List::cons(callable, args),
+        form: current_stack_entry.form,
+        try_frame: None,
+    });
+
+    Ok(())
+}
+
+/// Completes the native call outside of the evaluator's stack, by evaluating
+/// each callback separately. Used when `Op::apply` is called directly.
+pub(crate) fn iamlisp_eval_native_result(
+    mut result: NativeResult,
+    env: &Env,
+) -> anyhow::Result<Expression> {
+    loop {
+        result = match result {
+            NativeResult::Return(value) => return Ok(value),
+            NativeResult::Throw(value) => bail!("{}", error_message(&value)),
+            NativeResult::Call {
+                callable,
+                args,
+                then,
+            } => {
+                if !iamlisp_is_callable(&callable) {
+                    bail!(
+                        "Expression is not callable type: {} (args: {})",
+                        callable,
+                        args
+                    );
+                }
+
+                let (value, _) = iamlisp_eval_stack(StackEntry {
+                    input: list![],
+                    output: List::cons(callable, args),
+                    env: env.clone(),
+                    form: None,
+                    try_frame: None,
+                })?;
+
+                then(value)?
+            }
+        }
+    }
+}
+
 fn iamlisp_call_function(
     func: &Expression,
     args_values: &List<Expression>,
@@ -283,6 +688,7 @@ fn iamlisp_call_function(
             args: args_names,
             env,
             body,
+            ..
         }) => {
             let mut env = env.child();
 
@@ -295,17 +701,42 @@ fn iamlisp_call_function(
 
             body.push_top(begin_symbol!());
 
+            // The body is evaluated on behalf of the call form.
             call_stack.push_top(StackEntry {
                 env,
                 input: body,
                 output: list![],
+                form: current_stack_entry.form,
+                try_frame: None,
             });
 
             return Ok(());
         }
 
         Expression::Value(Value::NativeCall(c)) => {
-            c.apply(args_values, &current_stack_entry.env)?
+            match c.apply_with_callbacks(args_values, &current_stack_entry.env)? {
+                NativeResult::Return(result) => result,
+                NativeResult::Throw(value) => {
+                    let forms = iamlisp_stack_forms(&current_stack_entry.form, call_stack);
+                    let error = EvalError::locate(anyhow!("{}", error_message(&value)), forms);
+
+                    return iamlisp_unwind(value, error, call_stack);
+                }
+                NativeResult::Call {
+                    callable,
+                    args,
+                    then,
+                } => {
+                    return iamlisp_push_callback(
+                        c.name(),
+                        callable,
+                        args,
+                        then,
+                        current_stack_entry,
+                        call_stack,
+                    );
+                }
+            }
         }
 
         ex => {
@@ -334,90 +765,231 @@ pub(crate) fn iamlisp_pass_value_to_next_stack_entry(
     Ok(())
 }
 
+fn iamlisp_is_constant(expression: &Expression) -> bool {
+    match expression {
+        Expression::Value(Value::Vector(items)) => items.iter().all(iamlisp_is_constant),
+        Expression::Value(Value::Map(entries)) => iamlisp_is_constant_map(entries),
+        Expression::Value(Value::Set(items)) => items.iter().all(iamlisp_is_constant),
+        Expression::Value(_) => true,
+        _ => false,
+    }
+}
+
+fn iamlisp_is_constant_map(entries: &HashMap<Expression, Expression>) -> bool {
+    entries
+        .iter()
+        .all(|(key, value)| iamlisp_is_constant(key) && iamlisp_is_constant(value))
+}
+
+/// Collection literals like `[a (+ 1 2)]`, `#{a}` or `{:a (+ 1 2)}` contain expressions that should be
+/// evaluated, so they are evaluated as a call of the collection constructor. Items of sets and
+/// keys of maps are deduplicated by the constructor, after evaluation.
+fn iamlisp_literal_constructor(value: &Value) -> Option<List<Expression>> {
+    match value {
+        Value::Vector(items) if !items.iter().all(iamlisp_is_constant) => {
+            let constructor = NativeCall(Arc::new(Box::from(VectorConstructor)));
+
+            Some(
+                std::iter::once(constructor.into())
+                    .chain(items.iter().cloned())
+                    .collect(),
+            )
+        }
+        Value::Set(items) if !items.iter().all(iamlisp_is_constant) => {
+            let constructor = NativeCall(Arc::new(Box::from(SetConstructor)));
+
+            Some(
+                std::iter::once(constructor.into())
+                    .chain(items.iter().cloned())
+                    .collect(),
+            )
+        }
+        Value::Map(entries) if !iamlisp_is_constant_map(entries) => {
+            let constructor = NativeCall(Arc::new(Box::from(MapConstructor)));
+
+            Some(
+                std::iter::once(constructor.into())
+                    .chain(
+                        entries
+                            .iter()
+                            .flat_map(|(key, value)| [key.clone(), value.clone()]),
+                    )
+                    .collect(),
+            )
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
 pub(crate) fn iamlisp_eval(expr: &Expression, env: &Env) -> anyhow::Result<Expression> {
+    iamlisp_eval_with_stats(expr, env).map(|(result, _)| result)
+}
+
+pub(crate) fn iamlisp_eval_with_stats(
+    expr: &Expression,
+    env: &Env,
+) -> anyhow::Result<(Expression, EvalStats)> {
     Ok(match expr {
-        Expression::List(list) => iamlisp_eval_list(list, env)?,
-        Expression::Symbol(name) => get_from_env(name, env)?,
-        value => value.clone(),
+        Expression::List(list) => iamlisp_eval_stack(StackEntry {
+            input: List::clone(list),
+            output: list![],
+            env: env.clone(),
+            form: Some(list.clone()),
+            try_frame: None,
+        })?,
+        Expression::Symbol(name) => (get_from_env(name, env)?, EvalStats::default()),
+        Expression::Value(value) => match iamlisp_literal_constructor(value) {
+            Some(input) => iamlisp_eval_list_with_stats(&input, env)?,
+            None => (value.clone().into(), EvalStats::default()),
+        },
+        expression => (expression.clone(), EvalStats::default()),
     })
 }
 
+/// The last expression of `begin` is in tail position: its value is the value of
+/// the whole `begin`, so it can replace the `begin` entry on the stack. Lambda
+/// bodies and `cond` branches are evaluated through `begin` and get it for free.
+fn iamlisp_is_tail_position(stack_entry: &StackEntry) -> bool {
+    let is_begin = matches!(
+        stack_entry.output.head(),
+        Some(Expression::Value(Value::NativeCall(c))) if c.name() == "begin"
+    );
+
+    is_begin && stack_entry.input.is_empty()
+}
+
 pub(crate) fn iamlisp_eval_list(expr: &List<Expression>, env: &Env) -> anyhow::Result<Expression> {
-    let initial_entry = StackEntry {
-        input: expr.clone(),
-        output: list![],
-        env: env.clone(),
-    };
-    let mut stack = list![initial_entry];
+    iamlisp_eval_list_with_stats(expr, env).map(|(result, _)| result)
+}
 
-    let mut last_return_value = Value::Nil.into();
+fn iamlisp_eval_stack_entry(
+    mut stack_entry: StackEntry,
+    stack: &mut CallStack,
+    return_value: &mut Expression,
+) -> anyhow::Result<()> {
+    // The value in the output of the frame may look like any other form.
+    if iamlisp_is_try_frame(&stack_entry) {
+        return iamlisp_eval_try_frame(stack_entry, stack, return_value);
+    }
 
-    loop {
-        match stack.shift() {
-            Some(mut stack_entry) => {
-                if iamlisp_is_variables_definition(&mut stack_entry) {
-                    iamlisp_eval_variables_definition(stack_entry, &mut stack)?;
+    if iamlisp_is_macro_call(&stack_entry) {
+        return iamlisp_eval_macro_call(stack_entry, stack, return_value);
+    }
 
-                    continue;
-                }
+    if iamlisp_is_variables_definition(&stack_entry) {
+        return iamlisp_eval_variables_definition(stack_entry, stack, return_value);
+    }
 
-                if iamlisp_is_cond_expression(&mut stack_entry) {
-                    iamlisp_eval_cond_expression(stack_entry, &mut stack)?;
+    if iamlisp_is_cond_expression(&stack_entry) {
+        return iamlisp_eval_cond_expression(stack_entry, stack);
+    }
 
-                    continue;
-                }
+    if iamlisp_is_loop_expression(&stack_entry) {
+        return iamlisp_eval_loop_expression(stack_entry, stack, return_value);
+    }
 
-                if iamlisp_is_lambda_definition(&mut stack_entry) {
-                    iamlisp_eval_lambda_definition(
-                        stack_entry,
-                        &mut stack,
-                        &mut last_return_value,
-                    )?;
+    if iamlisp_is_recur_expression(&stack_entry) {
+        return iamlisp_eval_recur_expression(stack_entry, stack);
+    }
 
-                    continue;
-                }
+    if iamlisp_is_lambda_definition(&stack_entry) {
+        return iamlisp_eval_lambda_definition(stack_entry, stack, return_value);
+    }
 
-                if iamlisp_is_macro_definition(&mut stack_entry) {
-                    iamlisp_eval_macro_definition(stack_entry, &mut stack, &mut last_return_value)?;
+    if iamlisp_is_named_definition(&stack_entry) {
+        return iamlisp_eval_named_definition(stack_entry, stack, return_value);
+    }
 
-                    continue;
-                }
+    if iamlisp_is_macro_definition(&stack_entry) {
+        return iamlisp_eval_macro_definition(stack_entry, stack, return_value);
+    }
 
-                if iamlisp_is_quote_expression(&mut stack_entry) {
-                    iamlisp_eval_quote_expression(stack_entry, &mut stack, &mut last_return_value)?;
+    if iamlisp_is_quasiquote_expression(&stack_entry) {
+        return iamlisp_eval_quasiquote_expression(stack_entry, stack, return_value);
+    }
 
-                    continue;
-                }
+    if iamlisp_is_quote_expression(&stack_entry) {
+        return iamlisp_eval_quote_expression(stack_entry, stack, return_value);
+    }
 
-                match stack_entry.input.shift() {
-                    Some(expression) => {
-                        iamlisp_eval_next_input_expression(&expression, stack_entry, &mut stack)?;
-                    }
-                    None => match stack_entry.output.head() {
-                        Some(callable) => {
-                            iamlisp_call_function(
-                                &callable.clone(),
-                                &stack_entry.output.tail().clone(),
-                                stack_entry,
-                                &mut stack,
-                                &mut last_return_value,
-                            )?;
-                        }
-                        None => {
-                            iamlisp_pass_value_to_next_stack_entry(
-                                list![].into(),
-                                &mut stack,
-                                &mut last_return_value,
-                            )?;
-                        }
-                    },
-                }
-            }
-            None => return Ok(last_return_value),
+    if iamlisp_is_eval_expression(&stack_entry) {
+        return iamlisp_eval_eval_expression(stack_entry, stack, return_value);
+    }
+
+    if iamlisp_is_try_expression(&stack_entry) {
+        return iamlisp_eval_try_expression(stack_entry, stack);
+    }
+
+    match stack_entry.input.shift() {
+        Some(expression) if iamlisp_is_tail_position(&stack_entry) => {
+            iamlisp_eval_expression_in_place(expression, stack_entry.env, stack, return_value)
         }
+        Some(expression) => iamlisp_eval_next_input_expression(&expression, stack_entry, stack),
+        None => match stack_entry.output.head() {
+            Some(callable) => iamlisp_call_function(
+                &callable.clone(),
+                &stack_entry.output.tail().clone(),
+                stack_entry,
+                stack,
+                return_value,
+            ),
+            None => iamlisp_pass_value_to_next_stack_entry(list![].into(), stack, return_value),
+        },
     }
 }
 
+pub(crate) fn iamlisp_eval_list_with_stats(
+    expr: &List<Expression>,
+    env: &Env,
+) -> anyhow::Result<(Expression, EvalStats)> {
+    iamlisp_eval_stack(StackEntry {
+        input: expr.clone(),
+        output: list![],
+        env: env.clone(),
+        form: None,
+        try_frame: None,
+    })
+}
+
+pub(crate) fn iamlisp_eval_stack(entry: StackEntry) -> anyhow::Result<(Expression, EvalStats)> {
+    let mut stack = CallStack::default();
+
+    stack.push_top(entry);
+
+    let mut last_return_value = Value::Nil.into();
+    let mut steps = 0;
+
+    while let Some(stack_entry) = stack.shift() {
+        let form = stack_entry.form.clone();
+
+        steps += 1;
+
+        if let Err(error) =
+            iamlisp_eval_stack_entry(stack_entry, &mut stack, &mut last_return_value)
+        {
+            let error = EvalError::locate(error, iamlisp_stack_forms(&form, &stack));
+
+            iamlisp_unwind(error_value(&error), error, &mut stack)?;
+        }
+    }
+
+    let stats = EvalStats {
+        max_stack_depth: stack.max_depth,
+        steps,
+    };
+
+    Ok((last_return_value, stats))
+}
+
+/// Forms being evaluated, from the innermost one to the outermost.
+fn iamlisp_stack_forms(form: &Option<Rc<List<Expression>>>, stack: &CallStack) -> Vec<FormId> {
+    form.iter()
+        .chain(stack.entries.iter().rev().filter_map(|e| e.form.as_ref()))
+        .map(FormId::of)
+        .collect()
+}
+
 pub(crate) fn get_from_env(name: &'static str, env: &Env) -> anyhow::Result<Expression> {
     env.get(name)
         .ok_or_else(|| anyhow::anyhow!("Symbol {} is not defined", name))
@@ -427,6 +999,7 @@ pub(crate) fn get_from_env(name: &'static str, env: &Env) -> anyhow::Result<Expr
 mod tests {
     use super::*;
     use crate::eval::create_env;
+    use crate::quote_symbol;
 
     #[test]
     fn test_eval_empty_list_into_empty_list() {
@@ -435,7 +1008,7 @@ mod tests {
 
         let result = iamlisp_eval_list(&expr, &env).unwrap();
 
-        assert_eq!(Expression::List(Box::new(list![])), result)
+        assert_eq!(Expression::List(Rc::new(list![])), result)
     }
 
     #[test]
@@ -475,14 +1048,15 @@ mod tests {
 
         assert_eq!(
             Expression::Value(Value::Lambda {
-                args: Box::new(list![Expression::Symbol("a")]),
-                body: Box::new(list![list![
+                args: Rc::new(list![Expression::Symbol("a")]),
+                body: Rc::new(list![list![
                     Expression::Symbol("+"),
                     Expression::Symbol("a"),
                     Value::Int64(3).into()
                 ]
                 .into()]),
                 env,
+                doc: None,
             }),
             result
         )
@@ -526,13 +1100,14 @@ mod tests {
 
         assert_eq!(
             Expression::Value(Value::Macro {
-                args: Box::from(list![Expression::Symbol("a")]),
-                body: Box::from(list![list![
+                args: Rc::new(list![Expression::Symbol("a")]),
+                body: Rc::new(list![list![
                     Expression::Symbol("+"),
                     Expression::Symbol("a"),
                     Value::Int64(3).into()
                 ]
                 .into()]),
+                doc: None,
             }),
             result
         )
@@ -631,6 +1206,32 @@ mod tests {
         assert_eq!(None, env.get("a"));
     }
 
+    #[test]
+    fn test_tail_calls_do_not_grow_stack() {
+        let env = create_env();
+        let program = r#"
+            (defun count-down (n) (cond (= n 0) 0 (begin (def m (dec n)) (count-down m))))
+            (defun ping (n) (cond (= n 0) Nil (pong (dec n))))
+            (defun pong (n) (ping n))"#;
+
+        for expr in crate::read::parse(program).unwrap().iter() {
+            iamlisp_eval(expr, &env).unwrap();
+        }
+
+        let depth = |program: &str| match crate::read::parse(program).unwrap().head() {
+            Some(Expression::List(expr)) => {
+                let (_, stats) = iamlisp_eval_list_with_stats(expr, &env).unwrap();
+
+                stats.max_stack_depth
+            }
+            _ => unreachable!(),
+        };
+
+        assert_eq!(depth("(count-down 10)"), depth("(count-down 10000)"));
+        assert_eq!(depth("(ping 10)"), depth("(ping 10000)"));
+        assert!(depth("(ping 10000)") < 10);
+    }
+
     #[test]
     fn test_quote_special_symbol() {
         let env = create_env();
@@ -647,7 +1248,7 @@ mod tests {
         let result = iamlisp_eval_list(&expr, &env).unwrap();
 
         assert_eq!(
-            Expression::List(Box::from(list![
+            Expression::List(Rc::new(list![
                 Expression::Symbol("+"),
                 Expression::Symbol("a"),
                 Expression::Symbol("b")
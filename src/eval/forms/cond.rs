@@ -17,7 +17,7 @@ pub(crate) fn iamlisp_eval_cond_expression(
     let output_vec = stack_entry.output.iter().collect::<Vec<_>>();
 
     match output_vec.as_slice() {
-        &[] => match stack_entry.input.shift() {
+        [] => match stack_entry.input.shift() {
             Some(cond_symbol!()) => {
                 stack_entry.output.push(cond_symbol!());
 
@@ -30,7 +30,7 @@ pub(crate) fn iamlisp_eval_cond_expression(
                 );
             }
         },
-        &[cond_symbol!()] if stack_entry.input.len() == 1 => {
+        [cond_symbol!()] if stack_entry.input.len() <= 1 => {
             let default_expr = stack_entry
                 .input
                 .shift()
@@ -40,9 +40,11 @@ pub(crate) fn iamlisp_eval_cond_expression(
                 env: stack_entry.env.clone(),
                 input: list![begin_symbol!(), default_expr],
                 output: list![],
+                form: stack_entry.form,
+                try_frame: None,
             });
         }
-        &[cond_symbol!()] => match stack_entry.input.shift() {
+        [cond_symbol!()] => match stack_entry.input.shift() {
             Some(test_expr) => {
                 iamlisp_eval_next_input_expression(&test_expr, stack_entry, stack)?;
             }
@@ -50,13 +52,13 @@ pub(crate) fn iamlisp_eval_cond_expression(
                 bail!("Test expression is expected in cond construct");
             }
         },
-        &[cond_symbol!(), Expression::Value(Value::Bool(false))] => {
+        [cond_symbol!(), Expression::Value(Value::Bool(false))] => {
             let _ = stack_entry.input.shift();
             let _ = stack_entry.output.pop();
 
             stack.push_top(stack_entry);
         }
-        &[cond_symbol!(), Expression::Value(Value::Bool(true))] => {
+        [cond_symbol!(), Expression::Value(Value::Bool(true))] => {
             let true_expr = stack_entry
                 .input
                 .shift()
@@ -66,6 +68,8 @@ pub(crate) fn iamlisp_eval_cond_expression(
                 env: stack_entry.env.clone(),
                 input: list![begin_symbol!(), true_expr],
                 output: list![],
+                form: stack_entry.form,
+                try_frame: None,
             });
         }
         _ => bail!(
@@ -0,0 +1,57 @@
+use crate::eval::eval::{
+    iamlisp_eval_expression_in_place, iamlisp_eval_next_input_expression, CallStack, StackEntry,
+};
+use crate::eval::types::Expression;
+use crate::eval_symbol;
+use anyhow::bail;
+
+pub(crate) fn iamlisp_is_eval_expression(stack_entry: &StackEntry) -> bool {
+    let input_is_eval = matches!(stack_entry.input.head(), Some(eval_symbol!()));
+    let output_is_eval = matches!(stack_entry.output.head(), Some(eval_symbol!()));
+
+    input_is_eval || output_is_eval
+}
+
+/*
+ Eval expression:
+
+ []                  (eval (cons '+ '(1 2)))   {}
+
+ [eval]              ()                        {}
+ []                  (cons '+ '(1 2))
+
+ [eval (+ 1 2)]      ()                        {}
+
+ (+ 1 2)                                       {}
+
+ The value of the argument is evaluated in place of the `eval` expression, in the same environment.
+*/
+pub(crate) fn iamlisp_eval_eval_expression(
+    mut stack_entry: StackEntry,
+    stack: &mut CallStack,
+    return_value: &mut Expression,
+) -> anyhow::Result<()> {
+    let output_vec = stack_entry.output.iter().collect::<Vec<_>>();
+
+    match output_vec.as_slice() {
+        [] => {
+            let _ = stack_entry.input.shift();
+
+            let form = match (stack_entry.input.shift(), stack_entry.input.shift()) {
+                (Some(form), None) => form,
+                (None, _) => bail!("Too few parameters for special operator: EVAL"),
+                (Some(_), Some(_)) => bail!("Too many parameters for special operator: EVAL"),
+            };
+
+            stack_entry.output.push(eval_symbol!());
+
+            iamlisp_eval_next_input_expression(&form, stack_entry, stack)
+        }
+        [eval_symbol!(), form] => {
+            let form = (*form).clone();
+
+            iamlisp_eval_expression_in_place(form, stack_entry.env, stack, return_value)
+        }
+        _ => bail!("Unexpected eval output state: {}", stack_entry.output),
+    }
+}
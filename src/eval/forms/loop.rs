@@ -1,11 +1,15 @@
 use crate::data::List;
-use crate::eval::eval::{iamlisp_eval_next_input_expression, CallStack, StackEntry};
-use crate::eval::types::{Expression, Value};
-use crate::{begin_symbol, list, loop_symbol};
+use crate::eval::eval::{
+    iamlisp_eval_next_input_expression, iamlisp_pass_value_to_next_stack_entry, CallStack,
+    StackEntry,
+};
+use crate::eval::types::Expression;
+use crate::{begin_symbol, list, loop_symbol, recur_symbol};
 use anyhow::bail;
+use std::rc::Rc;
 
 fn is_even(n: usize) -> bool {
-    n % 2 == 0
+    n.is_multiple_of(2)
 }
 
 pub(crate) fn iamlisp_is_loop_expression(stack_entry: &StackEntry) -> bool {
@@ -15,19 +19,68 @@ pub(crate) fn iamlisp_is_loop_expression(stack_entry: &StackEntry) -> bool {
     input_is_loop || output_is_loop
 }
 
+fn iamlisp_push_loop_body(
+    stack_entry: StackEntry,
+    body: &Expression,
+    stack: &mut CallStack,
+) -> anyhow::Result<()> {
+    let mut input = match body {
+        Expression::List(body) => List::clone(body),
+        _ => bail!("Unexpected loop body: {}", body),
+    };
+
+    input.push_top(begin_symbol!());
+
+    let env = stack_entry.env.clone();
+
+    stack.push_top(stack_entry);
+    stack.push_top(StackEntry {
+        env,
+        input,
+        output: list![],
+        form: None,
+        try_frame: None,
+    });
+
+    Ok(())
+}
+
+/*
+ Loop expression:
+
+ []                             (loop (x 2) (cond (> x 0) (recur (- x 1)) x))   {}
+
+ [loop]                         ((x 2) (cond ...))                              {}
+
+ [loop (x) ((cond ...)) x]      ()                                              {}
+
+ [loop (x) ((cond ...)) x 2]    ()                                              {}
+
+ [loop (x) ((cond ...))]        ()                                              {x: 2}
+
+ []                             (begin (cond ...))                              {x: 2}
+ [loop (x) ((cond ...))]        ()
+
+ ... until (recur (- x 1)) rebinds {x: 1} and pushes the body again ...
+
+ [loop (x) ((cond ...)) 0]      ()                                              {x: 0}
+*/
 pub(crate) fn iamlisp_eval_loop_expression(
     mut stack_entry: StackEntry,
     stack: &mut CallStack,
+    return_value: &mut Expression,
 ) -> anyhow::Result<()> {
     let output_vec = stack_entry.output.iter().collect::<Vec<_>>();
 
     match output_vec.as_slice() {
-        &[] => match stack_entry.input.shift() {
+        [] => match stack_entry.input.shift() {
             Some(loop_symbol!()) => {
                 stack_entry.output.push(loop_symbol!());
                 stack_entry.env = stack_entry.env.child();
 
                 stack.push_top(stack_entry);
+
+                return Ok(());
             }
             _ => {
                 bail!(
@@ -36,15 +89,14 @@ pub(crate) fn iamlisp_eval_loop_expression(
                 );
             }
         },
-        &[loop_symbol!()] => match stack_entry.input.shift() {
+        [loop_symbol!()] => match stack_entry.input.shift() {
             Some(Expression::List(args_list)) => {
                 let mut args_names = list![];
-                let mut args_values = list![];
 
                 for arg in args_list.iter().enumerate() {
                     match arg {
                         (i, Expression::Symbol(name)) if is_even(i) => {
-                            args_names.push(name.clone());
+                            args_names.push(Expression::Symbol(name));
                         }
 
                         (i, e) if is_even(i) => {
@@ -54,23 +106,127 @@ pub(crate) fn iamlisp_eval_loop_expression(
                             );
                         }
 
-                        (_, expression) => {
-                            args_values.push(expression.clone());
-                        }
+                        _ => (),
                     }
                 }
 
-                todo!();
+                if !is_even(args_list.len() as usize) {
+                    bail!("Loop init definition should contain pairs of names and values");
+                }
+
+                let body = std::mem::take(&mut stack_entry.input);
+
+                stack_entry.output.push(args_names.into());
+                stack_entry.output.push(body.into());
+                stack_entry.input = Rc::unwrap_or_clone(args_list);
             }
             _ => {
                 bail!("Initial definition expression is expected in loop construct");
             }
         },
-        _ => bail!(
-            "Unexpected variable definition output state: {}",
-            stack_entry.output
-        ),
+        [loop_symbol!(), _, _, Expression::Symbol(name), value] => {
+            stack_entry.env.set(name, (*value).clone());
+
+            let _ = stack_entry.output.pop();
+            let _ = stack_entry.output.pop();
+        }
+        [loop_symbol!(), _, _, result] => {
+            return iamlisp_pass_value_to_next_stack_entry((*result).clone(), stack, return_value);
+        }
+        _ => bail!("Unexpected loop output state: {}", stack_entry.output),
     }
 
-    Ok(())
+    match (stack_entry.input.shift(), stack_entry.input.shift()) {
+        (Some(symbol), Some(expr)) => {
+            stack_entry.output.push(symbol);
+
+            iamlisp_eval_next_input_expression(&expr, stack_entry, stack)
+        }
+        _ => {
+            let body = stack_entry
+                .output
+                .iter()
+                .nth(2)
+                .cloned()
+                .unwrap_or_default();
+
+            iamlisp_push_loop_body(stack_entry, &body, stack)
+        }
+    }
+}
+
+pub(crate) fn iamlisp_is_recur_expression(stack_entry: &StackEntry) -> bool {
+    let input_is_recur = matches!(stack_entry.input.head(), Some(recur_symbol!()));
+    let output_is_recur = matches!(stack_entry.output.head(), Some(recur_symbol!()));
+
+    input_is_recur || output_is_recur
+}
+
+/*
+ Recur expression:
+
+ []                             (recur (- x 1))                                 {x: 2}
+ [loop (x) ((cond ...))]        ()
+
+ [recur]                        ((- x 1))                                       {x: 2}
+ [loop (x) ((cond ...))]        ()
+
+ [recur 1]                      ()                                              {x: 2}
+ [loop (x) ((cond ...))]        ()
+
+ []                             (begin (cond ...))                              {x: 1}
+ [loop (x) ((cond ...))]        ()
+*/
+pub(crate) fn iamlisp_eval_recur_expression(
+    mut stack_entry: StackEntry,
+    stack: &mut CallStack,
+) -> anyhow::Result<()> {
+    if stack_entry.output.is_empty() {
+        match stack_entry.input.shift() {
+            Some(recur_symbol!()) => {
+                stack_entry.output.push(recur_symbol!());
+            }
+            _ => {
+                bail!(
+                    "Unexpected recur expression input state: {}",
+                    stack_entry.input
+                );
+            }
+        }
+    }
+
+    if let Some(expr) = stack_entry.input.shift() {
+        return iamlisp_eval_next_input_expression(&expr, stack_entry, stack);
+    }
+
+    // Expressions in tail position replace their parent entry, so recur in tail
+    // position of the loop body sits right on top of the loop entry. A lambda
+    // called from the body also replaces its caller, but has its own scope.
+    let mut loop_entry = match stack.shift() {
+        Some(entry) if entry.env.is_same(&stack_entry.env) && entry.output.len() == 3 => entry,
+        _ => bail!("Recur can only be used in tail position of loop"),
+    };
+
+    let (names, body) = match loop_entry.output.iter().collect::<Vec<_>>().as_slice() {
+        [loop_symbol!(), Expression::List(names), body] => ((**names).clone(), (*body).clone()),
+        _ => bail!("Recur can only be used in tail position of loop"),
+    };
+
+    let values = stack_entry.output.tail();
+
+    if names.len() != values.len() {
+        bail!(
+            "Recur expects {} arguments, but {} given",
+            names.len(),
+            values.len()
+        );
+    }
+
+    for (name, value) in names.iter().zip(values.iter()) {
+        if let Expression::Symbol(name) = name {
+            loop_entry.env.set(name, value.clone());
+        }
+    }
+
+    iamlisp_push_loop_body(loop_entry, &body, stack)
 }
@@ -1,6 +1,19 @@
 mod cond;
+mod eval;
 mod r#loop;
+mod quasiquote;
 mod quote;
+mod r#try;
 
 pub(crate) use cond::{iamlisp_eval_cond_expression, iamlisp_is_cond_expression};
+pub(crate) use eval::{iamlisp_eval_eval_expression, iamlisp_is_eval_expression};
+pub(crate) use quasiquote::{iamlisp_eval_quasiquote_expression, iamlisp_is_quasiquote_expression};
 pub(crate) use quote::{iamlisp_eval_quote_expression, iamlisp_is_quote_expression};
+pub(crate) use r#loop::{
+    iamlisp_eval_loop_expression, iamlisp_eval_recur_expression, iamlisp_is_loop_expression,
+    iamlisp_is_recur_expression,
+};
+pub(crate) use r#try::{
+    iamlisp_eval_try_expression, iamlisp_eval_try_frame, iamlisp_is_try_expression,
+    iamlisp_is_try_frame, iamlisp_unwind, TryFrame,
+};
@@ -0,0 +1,129 @@
+use crate::data::List;
+use crate::eval::eval::{iamlisp_eval_expression_in_place, CallStack, StackEntry};
+use crate::eval::types::{Expression, Value};
+use crate::{
+    list, quasiquote_symbol, quote_symbol, symbol, unquote_splicing_symbol, unquote_symbol,
+};
+use anyhow::bail;
+
+pub(crate) fn iamlisp_is_quasiquote_expression(stack_entry: &StackEntry) -> bool {
+    matches!(stack_entry.input.head(), Some(quasiquote_symbol!()))
+}
+
+fn quoted(expression: Expression) -> Expression {
+    list![quote_symbol!(), expression].into()
+}
+
+/*
+ Quasiquote template expands into the code that builds it:
+
+ `(a ,b ,@c)  =>  (append (list (quote a)) (list b) c)
+
+ Unquotes inside nested quasiquote belong to the inner template and are kept as is.
+*/
+fn iamlisp_expand_quasiquote(template: &Expression, depth: usize) -> anyhow::Result<Expression> {
+    let items = match template {
+        Expression::List(items) => items,
+        Expression::Value(Value::Vector(items)) => {
+            let items = items.iter().cloned().collect::<List<_>>().into();
+
+            return Ok(list![symbol!("vec"), iamlisp_expand_quasiquote(&items, depth)?].into());
+        }
+        Expression::Symbol(_) | Expression::Dot => return Ok(quoted(template.clone())),
+        value => return Ok(value.clone()),
+    };
+
+    match items.iter().collect::<Vec<_>>().as_slice() {
+        [unquote_symbol!(), expression] if depth == 1 => return Ok((*expression).clone()),
+        [unquote_symbol!(), expression] => {
+            return Ok(list![
+                symbol!("list"),
+                quoted(unquote_symbol!()),
+                iamlisp_expand_quasiquote(expression, depth - 1)?
+            ]
+            .into());
+        }
+        [quasiquote_symbol!(), expression] => {
+            return Ok(list![
+                symbol!("list"),
+                quoted(quasiquote_symbol!()),
+                iamlisp_expand_quasiquote(expression, depth + 1)?
+            ]
+            .into());
+        }
+        [unquote_splicing_symbol!(), _] if depth == 1 => {
+            bail!(
+                "Unquote-splicing is allowed only inside of list: {}",
+                template
+            )
+        }
+        _ => (),
+    }
+
+    let mut segments = list![symbol!("append")];
+
+    for item in items.iter() {
+        let segment = match item {
+            Expression::List(inner) => match inner.iter().collect::<Vec<_>>().as_slice() {
+                [unquote_splicing_symbol!(), expression] if depth == 1 => (*expression).clone(),
+                _ => list![symbol!("list"), iamlisp_expand_quasiquote(item, depth)?].into(),
+            },
+            _ => list![symbol!("list"), iamlisp_expand_quasiquote(item, depth)?].into(),
+        };
+
+        segments.push(segment);
+    }
+
+    Ok(segments.into())
+}
+
+pub(crate) fn iamlisp_eval_quasiquote_expression(
+    mut stack_entry: StackEntry,
+    stack: &mut CallStack,
+    return_value: &mut Expression,
+) -> anyhow::Result<()> {
+    let template = match stack_entry.input.tail_mut().shift() {
+        Some(template) => template,
+        None => bail!("Too few parameters for special operator: QUASIQUOTE"),
+    };
+
+    let code = iamlisp_expand_quasiquote(&template, 1)?;
+
+    iamlisp_eval_expression_in_place(code, stack_entry.env, stack, return_value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read::parse;
+
+    fn expand(template: &str) -> String {
+        let template = parse(template).unwrap().shift().unwrap();
+
+        iamlisp_expand_quasiquote(&template, 1).unwrap().to_string()
+    }
+
+    #[test]
+    fn expand_atoms() {
+        assert_eq!(expand("a"), "(quote a)");
+        assert_eq!(expand("10"), "10");
+        assert_eq!(expand("()"), "(append)");
+    }
+
+    #[test]
+    fn expand_unquotes() {
+        assert_eq!(expand("(a ,b ,@c)"), "(append (list (quote a)) (list b) c)");
+        assert_eq!(
+            expand("(a (b ,c))"),
+            "(append (list (quote a)) (list (append (list (quote b)) (list c))))"
+        );
+    }
+
+    #[test]
+    fn expand_nested_quasiquote() {
+        assert_eq!(
+            expand("(a `(b ,c))"),
+            "(append (list (quote a)) (list (list (quote quasiquote) (append (list (quote b)) (list (list (quote unquote) (quote c)))))))"
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use crate::data::List;
+use crate::eval::env::Env;
+use crate::eval::eval::{
+    iamlisp_pass_value_to_next_stack_entry, iamlisp_push_callback, CallStack, StackEntry,
+};
+use crate::eval::native_calls::NativeResult;
+use crate::eval::types::{Expression, Value};
+use crate::{begin_symbol, list, try_symbol};
+use anyhow::bail;
+use std::rc::Rc;
+
+/// State of the `try` frame on the stack.
+pub(crate) enum TryFrame {
+    /// Waits for the value of the body, or of the handler if the body failed.
+    Value {
+        catch: Option<Expression>,
+        finally: Option<Expression>,
+    },
+    /// `finally` runs while the error unwinds, and the error keeps unwinding after it.
+    Unwind {
+        value: Expression,
+        error: anyhow::Error,
+    },
+}
+
+pub(crate) fn iamlisp_is_try_expression(stack_entry: &StackEntry) -> bool {
+    matches!(stack_entry.input.head(), Some(try_symbol!()))
+}
+
+pub(crate) fn iamlisp_is_try_frame(stack_entry: &StackEntry) -> bool {
+    stack_entry.try_frame.is_some()
+}
+
+fn clause<'a>(expression: &'a Expression, name: &str) -> Option<&'a List<Expression>> {
+    match expression {
+        Expression::List(list) if matches!(list.head(), Some(Expression::Symbol(s)) if *s == name) => {
+            Some(list)
+        }
+        _ => None,
+    }
+}
+
+fn handler_lambda(env: &Env, args: List<Expression>, body: List<Expression>) -> Expression {
+    Value::Lambda {
+        env: env.clone(),
+        args: Rc::new(args),
+        body: Rc::new(body),
+        doc: None,
+    }
+    .into()
+}
+
+/*
+ Try expression:
+
+ []                         (try (car 1) (catch e (error-message e)))   {}
+
+ []                         ()                                          {} <try: (lambda (e) ...)>
+ []                         (begin (car 1))
+
+ ... (car 1) fails, the stack is unwound up to the frame ...
+
+ []                         ()                                          {} <try>
+ [(lambda (e) ...) #<error>] ()
+
+ ["Function..."]            ()                                          {} <try>
+
+ "Function..."
+*/
+pub(crate) fn iamlisp_eval_try_expression(
+    mut stack_entry: StackEntry,
+    stack: &mut CallStack,
+) -> anyhow::Result<()> {
+    let _ = stack_entry.input.shift();
+
+    let mut body = list![];
+    let mut catch = None;
+    let mut finally = None;
+
+    for expression in std::mem::take(&mut stack_entry.input).iter() {
+        if let Some(clause) = clause(expression, "catch") {
+            let (name, handler) = match clause.tail().iter().collect::<Vec<_>>().as_slice() {
+                [Expression::Symbol(name), handler @ ..] => (
+                    *name,
+                    handler.iter().map(|e| (*e).clone()).collect::<List<_>>(),
+                ),
+                _ => bail!("Catch should bind the error to a symbol: {}", clause),
+            };
+
+            if catch.is_some() {
+                bail!("Try should have only one catch clause");
+            }
+
+            catch = Some(handler_lambda(
+                &stack_entry.env,
+                list![Expression::Symbol(name)],
+                handler,
+            ));
+        } else if let Some(clause) = clause(expression, "finally") {
+            if finally.is_some() {
+                bail!("Try should have only one finally clause");
+            }
+
+            finally = Some(handler_lambda(
+                &stack_entry.env,
+                list![],
+                clause.tail().clone(),
+            ));
+        } else if catch.is_some() || finally.is_some() {
+            bail!(
+                "Catch and finally should be the last in try: {}",
+                expression
+            );
+        } else {
+            body.push(expression.clone());
+        }
+    }
+
+    body.push_top(begin_symbol!());
+
+    let env = stack_entry.env.clone();
+    let form = stack_entry.form.clone();
+
+    stack_entry.try_frame = Some(TryFrame::Value { catch, finally });
+
+    stack.push_top(stack_entry);
+    stack.push_top(StackEntry {
+        env,
+        input: body,
+        output: list![],
+        form,
+        try_frame: None,
+    });
+
+    Ok(())
+}
+
+/// The body or the handler is done, and `finally` runs before its value is returned.
+/// After `finally` of an unwinding error, the error is unwound further.
+pub(crate) fn iamlisp_eval_try_frame(
+    mut stack_entry: StackEntry,
+    stack: &mut CallStack,
+    return_value: &mut Expression,
+) -> anyhow::Result<()> {
+    let finally = match stack_entry.try_frame.take() {
+        Some(TryFrame::Value { finally, .. }) => finally,
+        Some(TryFrame::Unwind { value, error }) => return iamlisp_unwind(value, error, stack),
+        None => bail!("Unexpected try frame state: {}", stack_entry.output),
+    };
+    let value = stack_entry.output.shift().unwrap_or_default();
+
+    let Some(finally) = finally else {
+        return iamlisp_pass_value_to_next_stack_entry(value, stack, return_value);
+    };
+
+    iamlisp_push_callback(
+        "finally",
+        finally,
+        list![],
+        Box::new(move |_| Ok(NativeResult::Return(value))),
+        stack_entry,
+        stack,
+    )
+}
+
+/// Pops the stack up to the nearest `try` frame and calls its handler with the
+/// error value. `finally` of the frames without handler is run on the way. If
+/// nothing catches the value, the error is returned.
+pub(crate) fn iamlisp_unwind(
+    value: Expression,
+    error: anyhow::Error,
+    stack: &mut CallStack,
+) -> anyhow::Result<()> {
+    while let Some(mut stack_entry) = stack.shift() {
+        // Frames already unwinding are left, so errors of `finally` replace their error.
+        let (catch, finally) = match stack_entry.try_frame.take() {
+            Some(TryFrame::Value { catch, finally }) => (catch, finally),
+            _ => continue,
+        };
+
+        let env = stack_entry.env.clone();
+        let form = stack_entry.form.clone();
+
+        let handler = match (catch, finally) {
+            (Some(catch), finally) => {
+                // Errors of the handler are not caught by the same frame.
+                stack_entry.try_frame = Some(TryFrame::Value {
+                    catch: None,
+                    finally,
+                });
+
+                list![catch, value]
+            }
+            (None, Some(finally)) => {
+                stack_entry.try_frame = Some(TryFrame::Unwind { value, error });
+
+                list![finally]
+            }
+            (None, None) => continue,
+        };
+
+        stack.push_top(stack_entry);
+        stack.push_top(StackEntry {
+            env,
+            input: list![],
+            output: handler,
+            form,
+            try_frame: None,
+        });
+
+        return Ok(());
+    }
+
+    Err(error)
+}
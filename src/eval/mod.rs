@@ -1,10 +1,13 @@
 use crate::data::List;
 use crate::eval::env::Env;
-use crate::eval::eval::iamlisp_eval;
+use crate::eval::eval::{iamlisp_eval_with_stats, EvalStats};
 use crate::eval::native_calls::load_native_calls;
 use crate::eval::types::Expression;
 
+pub(crate) mod convert;
 pub(crate) mod env;
+pub(crate) mod error;
+#[allow(clippy::module_inception)]
 pub(crate) mod eval;
 pub(crate) mod forms;
 pub(crate) mod native_calls;
@@ -19,12 +22,19 @@ pub(crate) fn create_env() -> Env {
     env
 }
 
-pub(crate) fn eval(expressions: &List<Expression>, env: &Env) -> anyhow::Result<Expression> {
+pub(crate) fn eval_with_stats(
+    expressions: &List<Expression>,
+    env: &Env,
+) -> anyhow::Result<(Expression, EvalStats)> {
     let mut last_result = Expression::default();
+    let mut stats = EvalStats::default();
 
     for expr in expressions.iter() {
-        last_result = iamlisp_eval(expr, env)?;
+        let (result, expr_stats) = iamlisp_eval_with_stats(expr, env)?;
+
+        last_result = result;
+        stats = stats.merge(expr_stats);
     }
 
-    Ok(last_result)
+    Ok((last_result, stats))
 }
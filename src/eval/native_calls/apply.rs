@@ -0,0 +1,47 @@
+use crate::data::List;
+use crate::eval::env::Env;
+use crate::eval::eval::iamlisp_eval_native_result;
+use crate::eval::native_calls::{NativeResult, Op};
+use crate::eval::types::{Expression, Value};
+use anyhow::bail;
+
+/// `(apply f a b '(c d))` calls `(f a b c d)`.
+#[derive(Clone, PartialEq)]
+pub(crate) struct Apply;
+
+impl Op for Apply {
+    fn name(&self) -> &'static str {
+        "apply"
+    }
+
+    fn apply(&self, args: &List<Expression>, env: &Env) -> anyhow::Result<Expression> {
+        iamlisp_eval_native_result(self.apply_with_callbacks(args, env)?, env)
+    }
+
+    fn apply_with_callbacks(
+        &self,
+        args: &List<Expression>,
+        _env: &Env,
+    ) -> anyhow::Result<NativeResult> {
+        let (func, func_args, spread_args) = match args.iter().collect::<Vec<_>>().as_slice() {
+            [func, func_args @ .., spread_args] => (*func, func_args.to_vec(), *spread_args),
+            _ => bail!("Expected function and list of arguments: {}", args),
+        };
+
+        let spread_args = match spread_args {
+            Expression::List(list) => list.iter().cloned().collect::<Vec<_>>(),
+            Expression::Value(Value::Vector(items)) => items.iter().cloned().collect(),
+            Expression::Value(Value::Nil) => vec![],
+            _ => bail!(
+                "Function not implemented for this kind of arguments: {}",
+                args
+            ),
+        };
+
+        let func_args = func_args.into_iter().cloned().chain(spread_args).collect();
+
+        Ok(NativeResult::call(func.clone(), func_args, |result| {
+            Ok(NativeResult::Return(result))
+        }))
+    }
+}
@@ -8,10 +8,10 @@ pub(crate) struct Begin;
 
 impl Op for Begin {
     fn name(&self) -> &'static str {
-        "/"
+        "begin"
     }
 
     fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
-        Ok(args.iter().last().map(Clone::clone).unwrap_or_default())
+        Ok(args.iter().last().cloned().unwrap_or_default())
     }
 }
@@ -1,7 +1,9 @@
 use crate::data::List;
 use crate::eval::env::Env;
+use crate::eval::native_calls::math::Number;
 use crate::eval::native_calls::Op;
 use crate::eval::types::{Expression, Value};
+use std::cmp::Ordering;
 
 fn bin_cmp<CMP>(cmp_fn: CMP, args: &List<Expression>) -> anyhow::Result<bool>
 where
@@ -14,19 +16,69 @@ where
     let mut args_iter = args.iter().peekable();
 
     while let Some(current_operand) = args_iter.next() {
-        match args_iter.peek() {
-            Some(next_operand) => {
-                if !cmp_fn(current_operand, next_operand)? {
-                    return Ok(false);
-                }
+        if let Some(next_operand) = args_iter.peek() {
+            if !cmp_fn(current_operand, next_operand)? {
+                return Ok(false);
             }
-            None => (),
         }
     }
 
     Ok(true)
 }
 
+/// Numbers are equal by exact value whatever their type, and lists, vectors and
+/// map values are equal item by item. Map keys and set items are looked up by
+/// hash, so they are equal only with the same type: `(= {1 :a} {1.0 :a})` is
+/// false, like `(has #{1} 1.0)`. Values of different types are not equal.
+pub(crate) fn values_equal(a: &Expression, b: &Expression) -> bool {
+    if let (Some(a), Some(b)) = (Number::from_expression(a), Number::from_expression(b)) {
+        return a.compare(&b) == Some(Ordering::Equal);
+    }
+
+    match (a, b) {
+        (Expression::List(a), Expression::List(b)) => {
+            a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| values_equal(a, b))
+        }
+        (Expression::Value(Value::Vector(a)), Expression::Value(Value::Vector(b))) => {
+            a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| values_equal(a, b))
+        }
+        (Expression::Value(Value::Map(a)), Expression::Value(Value::Map(b))) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(key, a)| b.get(key).is_some_and(|b| values_equal(a, b)))
+        }
+        (a, b) => a == b,
+    }
+}
+
+/// Order of numbers and strings. `None` if one of the numbers is `NaN`.
+pub(crate) fn compare_values(a: &Expression, b: &Expression) -> anyhow::Result<Option<Ordering>> {
+    if let (Some(a), Some(b)) = (Number::from_expression(a), Number::from_expression(b)) {
+        return Ok(a.compare(&b));
+    }
+
+    Ok(match (a, b) {
+        (Expression::Value(Value::String(a)), Expression::Value(Value::String(b))) => {
+            Some(a.cmp(b))
+        }
+        _ => anyhow::bail!(
+            "Cannot compare {} with {}: {} and {}",
+            a.type_name(),
+            b.type_name(),
+            a,
+            b
+        ),
+    })
+}
+
+fn ordered(args: &List<Expression>, expected: fn(Ordering) -> bool) -> anyhow::Result<Expression> {
+    Ok(Value::Bool(bin_cmp(
+        |a, b| Ok(compare_values(a, b)?.is_some_and(expected)),
+        args,
+    )?)
+    .into())
+}
+
 #[derive(Clone, PartialEq)]
 pub(crate) struct Eq;
 
@@ -36,25 +88,7 @@ impl Op for Eq {
     }
 
     fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
-        Ok(Value::Bool(bin_cmp(
-            |a, b| match (a, b) {
-                (Expression::Value(Value::Int64(a)), Expression::Value(Value::Int64(b))) => {
-                    Ok(a == b)
-                }
-                (Expression::Value(Value::Float64(a)), Expression::Value(Value::Float64(b))) => {
-                    Ok(a == b)
-                }
-                (Expression::Value(Value::String(a)), Expression::Value(Value::String(b))) => {
-                    Ok(a == b)
-                }
-                (Expression::Value(Value::Bool(a)), Expression::Value(Value::Bool(b))) => {
-                    Ok(a == b)
-                }
-                _ => unimplemented!(),
-            },
-            args,
-        )?)
-        .into())
+        Ok(Value::Bool(bin_cmp(|a, b| Ok(values_equal(a, b)), args)?).into())
     }
 }
 
@@ -67,25 +101,7 @@ impl Op for Ne {
     }
 
     fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
-        Ok(Value::Bool(bin_cmp(
-            |a, b| match (a, b) {
-                (Expression::Value(Value::Int64(a)), Expression::Value(Value::Int64(b))) => {
-                    Ok(a != b)
-                }
-                (Expression::Value(Value::Float64(a)), Expression::Value(Value::Float64(b))) => {
-                    Ok(a != b)
-                }
-                (Expression::Value(Value::String(a)), Expression::Value(Value::String(b))) => {
-                    Ok(a != b)
-                }
-                (Expression::Value(Value::Bool(a)), Expression::Value(Value::Bool(b))) => {
-                    Ok(a != b)
-                }
-                _ => unimplemented!(),
-            },
-            args,
-        )?)
-        .into())
+        Ok(Value::Bool(bin_cmp(|a, b| Ok(!values_equal(a, b)), args)?).into())
     }
 }
 
@@ -98,19 +114,7 @@ impl Op for Gt {
     }
 
     fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
-        Ok(Value::Bool(bin_cmp(
-            |a, b| match (a, b) {
-                (Expression::Value(Value::Int64(a)), Expression::Value(Value::Int64(b))) => {
-                    Ok(a > b)
-                }
-                (Expression::Value(Value::Float64(a)), Expression::Value(Value::Float64(b))) => {
-                    Ok(a > b)
-                }
-                _ => unimplemented!(),
-            },
-            args,
-        )?)
-        .into())
+        ordered(args, Ordering::is_gt)
     }
 }
 
@@ -123,19 +127,7 @@ impl Op for Lt {
     }
 
     fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
-        Ok(Value::Bool(bin_cmp(
-            |a, b| match (a, b) {
-                (Expression::Value(Value::Int64(a)), Expression::Value(Value::Int64(b))) => {
-                    Ok(a < b)
-                }
-                (Expression::Value(Value::Float64(a)), Expression::Value(Value::Float64(b))) => {
-                    Ok(a < b)
-                }
-                _ => unimplemented!(),
-            },
-            args,
-        )?)
-        .into())
+        ordered(args, Ordering::is_lt)
     }
 }
 
@@ -148,19 +140,7 @@ impl Op for Ge {
     }
 
     fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
-        Ok(Value::Bool(bin_cmp(
-            |a, b| match (a, b) {
-                (Expression::Value(Value::Int64(a)), Expression::Value(Value::Int64(b))) => {
-                    Ok(a >= b)
-                }
-                (Expression::Value(Value::Float64(a)), Expression::Value(Value::Float64(b))) => {
-                    Ok(a >= b)
-                }
-                _ => unimplemented!(),
-            },
-            args,
-        )?)
-        .into())
+        ordered(args, Ordering::is_ge)
     }
 }
 
@@ -173,18 +153,6 @@ impl Op for Le {
     }
 
     fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
-        Ok(Value::Bool(bin_cmp(
-            |a, b| match (a, b) {
-                (Expression::Value(Value::Int64(a)), Expression::Value(Value::Int64(b))) => {
-                    Ok(a <= b)
-                }
-                (Expression::Value(Value::Float64(a)), Expression::Value(Value::Float64(b))) => {
-                    Ok(a <= b)
-                }
-                _ => unimplemented!(),
-            },
-            args,
-        )?)
-        .into())
+        ordered(args, Ordering::is_le)
     }
 }
@@ -0,0 +1,28 @@
+use crate::data::List;
+use crate::eval::env::Env;
+use crate::eval::native_calls::Op;
+use crate::eval::types::{Expression, Value};
+use anyhow::bail;
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct Doc;
+
+impl Op for Doc {
+    fn name(&self) -> &'static str {
+        "doc"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        let doc = match args.iter().collect::<Vec<_>>().as_slice() {
+            [Expression::Value(Value::Lambda { doc, .. })] => doc.clone(),
+            [Expression::Value(Value::Macro { doc, .. })] => doc.clone(),
+            [Expression::Value(Value::NativeCall(_))] => None,
+            _ => bail!(
+                "Function not implemented for this kind of arguments: {}",
+                args
+            ),
+        };
+
+        Ok(doc.map(Value::String).unwrap_or(Value::Nil).into())
+    }
+}
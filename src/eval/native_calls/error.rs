@@ -0,0 +1,158 @@
+use crate::data::List;
+use crate::eval::env::Env;
+use crate::eval::error::DEFAULT_ERROR_KIND;
+use crate::eval::eval::iamlisp_eval_native_result;
+use crate::eval::native_calls::{NativeResult, Op};
+use crate::eval::types::{Expression, Value};
+use anyhow::bail;
+
+fn error_arg(args: &List<Expression>) -> anyhow::Result<(&String, &String, &Expression)> {
+    match args.iter().collect::<Vec<_>>().as_slice() {
+        [Expression::Value(Value::Error {
+            kind,
+            message,
+            data,
+        })] => Ok((kind, message, data)),
+        _ => bail!(
+            "Function not implemented for this kind of arguments: {}",
+            args
+        ),
+    }
+}
+
+/// `(error "message")`, `(error :kind "message")` or `(error :kind "message" data)`.
+#[derive(Clone, PartialEq)]
+pub(crate) struct ErrorConstructor;
+
+impl Op for ErrorConstructor {
+    fn name(&self) -> &'static str {
+        "error"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        let (kind, message, data) = match args.iter().collect::<Vec<_>>().as_slice() {
+            [Expression::Value(Value::String(message))] => (
+                DEFAULT_ERROR_KIND.to_string(),
+                message,
+                Expression::default(),
+            ),
+            [Expression::Value(Value::Keyword(kind)), Expression::Value(Value::String(message))] => {
+                (kind.clone(), message, Expression::default())
+            }
+            [Expression::Value(Value::Keyword(kind)), Expression::Value(Value::String(message)), data] => {
+                (kind.clone(), message, (*data).clone())
+            }
+            _ => bail!(
+                "Expected optional kind, message and optional data: {}",
+                args
+            ),
+        };
+
+        Ok(Value::Error {
+            kind,
+            message: message.clone(),
+            data: Box::new(data),
+        }
+        .into())
+    }
+}
+
+/// `(throw value)` unwinds the evaluation up to the nearest `try`. Values other
+/// than errors are thrown as errors of kind `:thrown` with the value as data.
+#[derive(Clone, PartialEq)]
+pub(crate) struct Throw;
+
+impl Op for Throw {
+    fn name(&self) -> &'static str {
+        "throw"
+    }
+
+    fn apply(&self, args: &List<Expression>, env: &Env) -> anyhow::Result<Expression> {
+        iamlisp_eval_native_result(self.apply_with_callbacks(args, env)?, env)
+    }
+
+    fn apply_with_callbacks(
+        &self,
+        args: &List<Expression>,
+        _env: &Env,
+    ) -> anyhow::Result<NativeResult> {
+        let error = match args.iter().collect::<Vec<_>>().as_slice() {
+            [error @ Expression::Value(Value::Error { .. })] => (*error).clone(),
+            [value] => Value::Error {
+                kind: "thrown".to_string(),
+                message: match value {
+                    Expression::Value(Value::String(message)) => message.clone(),
+                    value => value.to_string(),
+                },
+                data: Box::new((*value).clone()),
+            }
+            .into(),
+            _ => bail!("Expected exactly one value to throw: {}", args),
+        };
+
+        Ok(NativeResult::Throw(error))
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct IsError;
+
+impl Op for IsError {
+    fn name(&self) -> &'static str {
+        "error?"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        match args.iter().collect::<Vec<_>>().as_slice() {
+            [value] => {
+                Ok(Value::Bool(matches!(value, Expression::Value(Value::Error { .. }))).into())
+            }
+            _ => bail!("Expected exactly one argument: {}", args),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct ErrorKind;
+
+impl Op for ErrorKind {
+    fn name(&self) -> &'static str {
+        "error-kind"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        let (kind, _, _) = error_arg(args)?;
+
+        Ok(Value::Keyword(kind.clone()).into())
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct ErrorMessage;
+
+impl Op for ErrorMessage {
+    fn name(&self) -> &'static str {
+        "error-message"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        let (_, message, _) = error_arg(args)?;
+
+        Ok(Value::String(message.clone()).into())
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct ErrorData;
+
+impl Op for ErrorData {
+    fn name(&self) -> &'static str {
+        "error-data"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        let (_, _, data) = error_arg(args)?;
+
+        Ok(data.clone())
+    }
+}
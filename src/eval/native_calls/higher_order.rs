@@ -0,0 +1,356 @@
+use crate::data::List;
+use crate::eval::env::Env;
+use crate::eval::eval::iamlisp_eval_native_result;
+use crate::eval::native_calls::cmp::values_equal;
+use crate::eval::native_calls::list_ops::{sequence, zip_items, SeqKind};
+use crate::eval::native_calls::{NativeResult, Op};
+use crate::eval::types::{Expression, Value};
+use crate::list;
+use anyhow::bail;
+use std::ops::ControlFlow;
+use std::vec::IntoIter;
+
+/*
+ Calls `func` for each item in turn, on the evaluator's stack:
+
+ (filter odd? '(1 2 3))
+
+ [odd? 1] -> true   {kept: (1)}
+ [odd? 2] -> false  {kept: (1)}
+ [odd? 3] -> true   {kept: (1 3)}
+
+ `args` makes arguments of the call from the state and the item, `step` folds
+ the result into the state or breaks with the result of the whole call, and
+ `done` makes the result when items are over.
+*/
+fn fold_calls<T, S, A, P, D>(
+    func: Expression,
+    mut items: IntoIter<T>,
+    state: S,
+    args: A,
+    step: P,
+    done: D,
+) -> NativeResult
+where
+    T: 'static,
+    S: 'static,
+    A: Fn(&S, &T) -> List<Expression> + 'static,
+    P: Fn(S, T, Expression) -> anyhow::Result<ControlFlow<Expression, S>> + 'static,
+    D: FnOnce(S) -> Expression + 'static,
+{
+    let item = match items.next() {
+        Some(item) => item,
+        None => return NativeResult::Return(done(state)),
+    };
+
+    NativeResult::call(func.clone(), args(&state, &item), move |result| {
+        Ok(match step(state, item, result)? {
+            ControlFlow::Break(result) => NativeResult::Return(result),
+            ControlFlow::Continue(state) => fold_calls(func, items, state, args, step, done),
+        })
+    })
+}
+
+fn predicate_result(result: Expression) -> anyhow::Result<bool> {
+    match result {
+        Expression::Value(Value::Bool(bool)) => Ok(bool),
+        x => bail!("Predicate should return Bool, got: {}", x),
+    }
+}
+
+/// Function and a list, vector or set it's applied to.
+fn func_and_sequence(
+    args: &List<Expression>,
+) -> anyhow::Result<(Expression, SeqKind, Vec<Expression>)> {
+    match args.iter().collect::<Vec<_>>().as_slice() {
+        [func, coll] => match sequence(coll) {
+            Some((kind, items)) => Ok(((*func).clone(), kind, items)),
+            None => bail!(
+                "Function not implemented for this kind of arguments: {}",
+                args
+            ),
+        },
+        _ => bail!("Expected function and collection: {}", args),
+    }
+}
+
+fn single_arg<S>(_: &S, item: &Expression) -> List<Expression> {
+    list![item.clone()]
+}
+
+/// `(map f coll ...)` gives the same kind of sequence as the first collection.
+/// With several collections `f` gets an item of each, as long as the shortest
+/// collection.
+#[derive(Clone, PartialEq)]
+pub(crate) struct Map;
+
+impl Op for Map {
+    fn name(&self) -> &'static str {
+        "map"
+    }
+
+    fn apply(&self, args: &List<Expression>, env: &Env) -> anyhow::Result<Expression> {
+        iamlisp_eval_native_result(self.apply_with_callbacks(args, env)?, env)
+    }
+
+    fn apply_with_callbacks(
+        &self,
+        args: &List<Expression>,
+        _env: &Env,
+    ) -> anyhow::Result<NativeResult> {
+        let (func, colls) = match args.iter().collect::<Vec<_>>().as_slice() {
+            [func, colls @ ..] if !colls.is_empty() => ((*func).clone(), colls.to_vec()),
+            _ => bail!("Expected function and collections: {}", args),
+        };
+
+        let sequences = match colls.into_iter().map(sequence).collect::<Option<Vec<_>>>() {
+            Some(sequences) => sequences,
+            None => bail!(
+                "Function not implemented for this kind of arguments: {}",
+                args
+            ),
+        };
+
+        let kind = sequences[0].0;
+        let calls = zip_items(sequences.into_iter().map(|(_, items)| items).collect());
+
+        Ok(fold_calls(
+            func,
+            calls.into_iter(),
+            vec![],
+            |_, call_args| call_args.iter().cloned().collect(),
+            |mut results, _, result| {
+                results.push(result);
+
+                Ok(ControlFlow::Continue(results))
+            },
+            move |results| kind.collect(results),
+        ))
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct Filter;
+
+impl Op for Filter {
+    fn name(&self) -> &'static str {
+        "filter"
+    }
+
+    fn apply(&self, args: &List<Expression>, env: &Env) -> anyhow::Result<Expression> {
+        iamlisp_eval_native_result(self.apply_with_callbacks(args, env)?, env)
+    }
+
+    fn apply_with_callbacks(
+        &self,
+        args: &List<Expression>,
+        _env: &Env,
+    ) -> anyhow::Result<NativeResult> {
+        let (pred, kind, items) = func_and_sequence(args)?;
+
+        Ok(fold_calls(
+            pred,
+            items.into_iter(),
+            vec![],
+            single_arg,
+            |mut kept, item, result| {
+                if predicate_result(result)? {
+                    kept.push(item);
+                }
+
+                Ok(ControlFlow::Continue(kept))
+            },
+            move |kept| kind.collect(kept),
+        ))
+    }
+}
+
+/// `(reduce f coll)` or `(reduce f init coll)`. Without `init` the first item
+/// is the initial value.
+#[derive(Clone, PartialEq)]
+pub(crate) struct Reduce;
+
+impl Op for Reduce {
+    fn name(&self) -> &'static str {
+        "reduce"
+    }
+
+    fn apply(&self, args: &List<Expression>, env: &Env) -> anyhow::Result<Expression> {
+        iamlisp_eval_native_result(self.apply_with_callbacks(args, env)?, env)
+    }
+
+    fn apply_with_callbacks(
+        &self,
+        args: &List<Expression>,
+        _env: &Env,
+    ) -> anyhow::Result<NativeResult> {
+        let (func, init, coll) = match args.iter().collect::<Vec<_>>().as_slice() {
+            [func, coll] => ((*func).clone(), None, *coll),
+            [func, init, coll] => ((*func).clone(), Some((*init).clone()), *coll),
+            _ => bail!(
+                "Expected function, optional initial value and collection: {}",
+                args
+            ),
+        };
+
+        let mut items = match sequence(coll) {
+            Some((_, items)) => items.into_iter(),
+            None => bail!(
+                "Function not implemented for this kind of arguments: {}",
+                args
+            ),
+        };
+
+        let init = match init.or_else(|| items.next()) {
+            Some(init) => init,
+            None => bail!("Reduce of empty collection without initial value"),
+        };
+
+        Ok(fold_calls(
+            func,
+            items,
+            init,
+            |acc, item| list![acc.clone(), item.clone()],
+            |_, _, result| Ok(ControlFlow::Continue(result)),
+            |acc| acc,
+        ))
+    }
+}
+
+/// `(find pred coll)` gives the first item matching the predicate, or `Nil`.
+#[derive(Clone, PartialEq)]
+pub(crate) struct Find;
+
+impl Op for Find {
+    fn name(&self) -> &'static str {
+        "find"
+    }
+
+    fn apply(&self, args: &List<Expression>, env: &Env) -> anyhow::Result<Expression> {
+        iamlisp_eval_native_result(self.apply_with_callbacks(args, env)?, env)
+    }
+
+    fn apply_with_callbacks(
+        &self,
+        args: &List<Expression>,
+        _env: &Env,
+    ) -> anyhow::Result<NativeResult> {
+        let (pred, _, items) = func_and_sequence(args)?;
+
+        Ok(fold_calls(
+            pred,
+            items.into_iter(),
+            (),
+            single_arg,
+            |_, item, result| {
+                Ok(match predicate_result(result)? {
+                    true => ControlFlow::Break(item),
+                    false => ControlFlow::Continue(()),
+                })
+            },
+            |_| Expression::default(),
+        ))
+    }
+}
+
+/// `(some pred coll)` tells whether any item matches the predicate.
+#[derive(Clone, PartialEq)]
+pub(crate) struct Any;
+
+impl Op for Any {
+    fn name(&self) -> &'static str {
+        "some"
+    }
+
+    fn apply(&self, args: &List<Expression>, env: &Env) -> anyhow::Result<Expression> {
+        iamlisp_eval_native_result(self.apply_with_callbacks(args, env)?, env)
+    }
+
+    fn apply_with_callbacks(
+        &self,
+        args: &List<Expression>,
+        _env: &Env,
+    ) -> anyhow::Result<NativeResult> {
+        let (pred, _, items) = func_and_sequence(args)?;
+
+        Ok(fold_calls(
+            pred,
+            items.into_iter(),
+            (),
+            single_arg,
+            |_, _, result| {
+                Ok(match predicate_result(result)? {
+                    true => ControlFlow::Break(Value::Bool(true).into()),
+                    false => ControlFlow::Continue(()),
+                })
+            },
+            |_| Value::Bool(false).into(),
+        ))
+    }
+}
+
+/// `(every? pred coll)` tells whether all items match the predicate.
+#[derive(Clone, PartialEq)]
+pub(crate) struct Every;
+
+impl Op for Every {
+    fn name(&self) -> &'static str {
+        "every?"
+    }
+
+    fn apply(&self, args: &List<Expression>, env: &Env) -> anyhow::Result<Expression> {
+        iamlisp_eval_native_result(self.apply_with_callbacks(args, env)?, env)
+    }
+
+    fn apply_with_callbacks(
+        &self,
+        args: &List<Expression>,
+        _env: &Env,
+    ) -> anyhow::Result<NativeResult> {
+        let (pred, _, items) = func_and_sequence(args)?;
+
+        Ok(fold_calls(
+            pred,
+            items.into_iter(),
+            (),
+            single_arg,
+            |_, _, result| {
+                Ok(match predicate_result(result)? {
+                    true => ControlFlow::Continue(()),
+                    false => ControlFlow::Break(Value::Bool(false).into()),
+                })
+            },
+            |_| Value::Bool(true).into(),
+        ))
+    }
+}
+
+/// `(includes? coll item)` tells whether the list, vector or set contains the
+/// item, or the string contains the substring.
+#[derive(Clone, PartialEq)]
+pub(crate) struct Includes;
+
+impl Op for Includes {
+    fn name(&self) -> &'static str {
+        "includes?"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        let includes = match args.iter().collect::<Vec<_>>().as_slice() {
+            [Expression::Value(Value::Set(items)), item] => items.contains(*item),
+            [Expression::Value(Value::String(string)), Expression::Value(Value::String(part))] => {
+                string.contains(part.as_str())
+            }
+            [coll, item] => match sequence(coll) {
+                Some((_, items)) => items.iter().any(|i| values_equal(i, item)),
+                None => bail!(
+                    "Function not implemented for this kind of arguments: {}",
+                    args
+                ),
+            },
+            _ => bail!("Expected collection and item: {}", args),
+        };
+
+        Ok(Value::Bool(includes).into())
+    }
+}
@@ -0,0 +1,28 @@
+use crate::data::List;
+use crate::eval::env::Env;
+use crate::eval::native_calls::Op;
+use crate::eval::types::{Expression, Value};
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct Print;
+
+impl Op for Print {
+    fn name(&self) -> &'static str {
+        "print"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        let line = args
+            .iter()
+            .map(|arg| match arg {
+                Expression::Value(Value::String(string)) => string.clone(),
+                arg => arg.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        println!("{}", line);
+
+        Ok(Value::Nil.into())
+    }
+}
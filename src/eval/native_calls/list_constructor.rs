@@ -1,8 +1,7 @@
 use crate::data::List;
 use crate::eval::env::Env;
 use crate::eval::native_calls::Op;
-use crate::eval::types::{Expression, Value};
-use anyhow::bail;
+use crate::eval::types::Expression;
 
 #[derive(Clone, PartialEq)]
 pub(crate) struct ListConstructor;
@@ -20,14 +19,16 @@ impl Op for ListConstructor {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::eval::types::Value;
     use crate::list;
+    use std::rc::Rc;
 
     #[test]
     fn empty_list_construct() {
         let env = Env::new();
 
         assert_eq!(
-            Expression::List(Box::from(list![])),
+            Expression::List(Rc::new(list![])),
             ListConstructor.apply(&list![], &env).unwrap()
         );
     }
@@ -37,7 +38,7 @@ mod tests {
         let env = Env::new();
 
         assert_eq!(
-            Expression::List(Box::from(list![
+            Expression::List(Rc::new(list![
                 Value::Int64(10).into(),
                 Value::Float64(10.0).into()
             ])),
@@ -0,0 +1,350 @@
+use crate::data::List;
+use crate::eval::env::Env;
+use crate::eval::native_calls::map::entry_to_pair;
+use crate::eval::native_calls::vector::Count;
+use crate::eval::native_calls::Op;
+use crate::eval::types::{Expression, Value};
+use anyhow::bail;
+
+/// Kind of the sequence, so functions can return the same kind they get.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum SeqKind {
+    List,
+    Vector,
+    Set,
+}
+
+impl SeqKind {
+    pub(crate) fn collect(self, items: impl IntoIterator<Item = Expression>) -> Expression {
+        match self {
+            SeqKind::List => items.into_iter().collect::<List<_>>().into(),
+            SeqKind::Vector => Value::Vector(items.into_iter().collect()).into(),
+            SeqKind::Set => Value::Set(items.into_iter().collect()).into(),
+        }
+    }
+}
+
+/// Items of a list, vector or set. `Nil` is an empty list.
+pub(crate) fn sequence(expression: &Expression) -> Option<(SeqKind, Vec<Expression>)> {
+    match expression {
+        Expression::List(list) => Some((SeqKind::List, list.iter().cloned().collect())),
+        Expression::Value(Value::Vector(items)) => {
+            Some((SeqKind::Vector, items.iter().cloned().collect()))
+        }
+        Expression::Value(Value::Set(items)) => {
+            Some((SeqKind::Set, items.iter().cloned().collect()))
+        }
+        Expression::Value(Value::Nil) => Some((SeqKind::List, vec![])),
+        _ => None,
+    }
+}
+
+/// Same as `sequence`, for functions which need the order of items.
+fn ordered_sequence(expression: &Expression) -> Option<(SeqKind, Vec<Expression>)> {
+    match sequence(expression)? {
+        (SeqKind::Set, _) => None,
+        sequence => Some(sequence),
+    }
+}
+
+fn count_arg(count: &Expression) -> anyhow::Result<usize> {
+    match count {
+        Expression::Value(Value::Int64(count)) if *count >= 0 => Ok(*count as usize),
+        x => bail!("Count should be non-negative Int64, got: {}", x),
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct Cons;
+
+impl Op for Cons {
+    fn name(&self) -> &'static str {
+        "cons"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        match args.iter().collect::<Vec<_>>().as_slice() {
+            [car, Expression::List(cdr)] => Ok(List::cons((*car).clone(), List::clone(cdr)).into()),
+            [car, cdr] => match sequence(cdr) {
+                Some((_, items)) => Ok(std::iter::once((*car).clone())
+                    .chain(items)
+                    .collect::<List<_>>()
+                    .into()),
+                None => bail!(
+                    "Function not implemented for this kind of arguments: {}",
+                    args
+                ),
+            },
+            _ => bail!(
+                "Function not implemented for this kind of arguments: {}",
+                args
+            ),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct Reverse;
+
+impl Op for Reverse {
+    fn name(&self) -> &'static str {
+        "reverse"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        match args.iter().collect::<Vec<_>>().as_slice() {
+            [Expression::List(list)] => Ok(List::clone(list).reverse().into()),
+            [Expression::Value(Value::Vector(items))] => {
+                Ok(Value::Vector(items.iter().rev().cloned().collect()).into())
+            }
+            _ => bail!(
+                "Function not implemented for this kind of arguments: {}",
+                args
+            ),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct Append;
+
+impl Op for Append {
+    fn name(&self) -> &'static str {
+        "append"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        let mut kind = None;
+        let mut result = vec![];
+
+        for arg in args.iter() {
+            match sequence(arg) {
+                Some((arg_kind, items)) => {
+                    kind.get_or_insert(arg_kind);
+                    result.extend(items);
+                }
+                None => bail!(
+                    "Function not implemented for this kind of arguments: {}",
+                    args
+                ),
+            }
+        }
+
+        Ok(kind.unwrap_or(SeqKind::List).collect(result))
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct IntoList;
+
+impl Op for IntoList {
+    fn name(&self) -> &'static str {
+        "to-list"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        let list = match args.iter().collect::<Vec<_>>().as_slice() {
+            [Expression::List(list)] => List::clone(list),
+            [Expression::Value(Value::Vector(items))] => items.iter().cloned().collect(),
+            [Expression::Value(Value::Set(items))] => items.iter().cloned().collect(),
+            [Expression::Value(Value::Map(entries))] => entries
+                .iter()
+                .map(|(key, value)| entry_to_pair(key, value))
+                .collect(),
+            [Expression::Value(Value::Nil)] => List::new(),
+            _ => bail!(
+                "Function not implemented for this kind of arguments: {}",
+                args
+            ),
+        };
+
+        Ok(list.into())
+    }
+}
+
+/// `car` and `first` are the same function.
+#[derive(Clone, PartialEq)]
+pub(crate) struct First(pub(crate) &'static str);
+
+impl Op for First {
+    fn name(&self) -> &'static str {
+        self.0
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        match args.iter().map(sequence).collect::<Vec<_>>().as_slice() {
+            [Some((_, items))] => Ok(items.first().cloned().unwrap_or_default()),
+            _ => bail!(
+                "Function not implemented for this kind of arguments: {}",
+                args
+            ),
+        }
+    }
+}
+
+/// `cdr` and `rest` are the same function, giving a list of all items but the
+/// first one.
+#[derive(Clone, PartialEq)]
+pub(crate) struct Rest(pub(crate) &'static str);
+
+impl Op for Rest {
+    fn name(&self) -> &'static str {
+        self.0
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        match args.iter().collect::<Vec<_>>().as_slice() {
+            [Expression::List(list)] => Ok(list.tail().clone().into()),
+            [arg] => match sequence(arg) {
+                Some((_, items)) => Ok(items.into_iter().skip(1).collect::<List<_>>().into()),
+                None => bail!(
+                    "Function not implemented for this kind of arguments: {}",
+                    args
+                ),
+            },
+            _ => bail!(
+                "Function not implemented for this kind of arguments: {}",
+                args
+            ),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct Length;
+
+impl Op for Length {
+    fn name(&self) -> &'static str {
+        "length"
+    }
+
+    fn apply(&self, args: &List<Expression>, env: &Env) -> anyhow::Result<Expression> {
+        Count.apply(args, env)
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct TakeItems;
+
+impl Op for TakeItems {
+    fn name(&self) -> &'static str {
+        "take"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        match args.iter().collect::<Vec<_>>().as_slice() {
+            [count, coll] => match ordered_sequence(coll) {
+                Some((kind, items)) => Ok(kind.collect(items.into_iter().take(count_arg(count)?))),
+                None => bail!(
+                    "Function not implemented for this kind of arguments: {}",
+                    args
+                ),
+            },
+            _ => bail!("Expected count and collection: {}", args),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct DropItems;
+
+impl Op for DropItems {
+    fn name(&self) -> &'static str {
+        "drop"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        match args.iter().collect::<Vec<_>>().as_slice() {
+            [count, coll] => match ordered_sequence(coll) {
+                Some((kind, items)) => Ok(kind.collect(items.into_iter().skip(count_arg(count)?))),
+                None => bail!(
+                    "Function not implemented for this kind of arguments: {}",
+                    args
+                ),
+            },
+            _ => bail!("Expected count and collection: {}", args),
+        }
+    }
+}
+
+/// `(range end)`, `(range start end)` or `(range start end step)` gives a list
+/// of integers from `start` up to, but not including, `end`.
+#[derive(Clone, PartialEq)]
+pub(crate) struct Range;
+
+impl Op for Range {
+    fn name(&self) -> &'static str {
+        "range"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        let ints = args
+            .iter()
+            .map(|arg| match arg {
+                Expression::Value(Value::Int64(int)) => Ok(*int),
+                x => bail!("Range bounds should be Int64, got: {}", x),
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let (start, end, step) = match ints.as_slice() {
+            [end] => (0, *end, 1),
+            [start, end] => (*start, *end, 1),
+            [_, _, 0] => bail!("Range step should not be zero"),
+            [start, end, step] => (*start, *end, *step),
+            _ => bail!("Expected end, or start, end and optional step: {}", args),
+        };
+
+        let items = std::iter::successors(Some(start), |i| i.checked_add(step))
+            .take_while(|i| if step > 0 { *i < end } else { *i > end })
+            .map(|i| Value::Int64(i).into())
+            .collect::<List<Expression>>();
+
+        Ok(items.into())
+    }
+}
+
+/// `(zip '(1 2) [:a :b])` gives a list of vectors `([1 :a] [2 :b])`, as long as
+/// the shortest sequence.
+#[derive(Clone, PartialEq)]
+pub(crate) struct Zip;
+
+impl Op for Zip {
+    fn name(&self) -> &'static str {
+        "zip"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        let sequences = match args
+            .iter()
+            .map(ordered_sequence)
+            .collect::<Option<Vec<_>>>()
+        {
+            Some(sequences) => sequences,
+            None => bail!(
+                "Function not implemented for this kind of arguments: {}",
+                args
+            ),
+        };
+
+        Ok(
+            zip_items(sequences.into_iter().map(|(_, items)| items).collect())
+                .into_iter()
+                .map(|items| SeqKind::Vector.collect(items))
+                .collect::<List<_>>()
+                .into(),
+        )
+    }
+}
+
+/// Groups items of the sequences by their position, as long as the shortest one.
+pub(crate) fn zip_items(sequences: Vec<Vec<Expression>>) -> Vec<Vec<Expression>> {
+    let len = sequences.iter().map(Vec::len).min().unwrap_or(0);
+    let mut iters = sequences
+        .into_iter()
+        .map(Vec::into_iter)
+        .collect::<Vec<_>>();
+
+    (0..len)
+        .map(|_| iters.iter_mut().filter_map(Iterator::next).collect())
+        .collect()
+}
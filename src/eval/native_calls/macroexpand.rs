@@ -0,0 +1,215 @@
+use crate::data::List;
+use crate::eval::env::Env;
+use crate::eval::eval::iamlisp_macroexpand_1;
+use crate::eval::native_calls::Op;
+use crate::eval::types::{Expression, Value};
+use crate::{
+    def_symbol, list, loop_symbol, quasiquote_symbol, quote_symbol, try_symbol,
+    unquote_splicing_symbol, unquote_symbol,
+};
+use anyhow::bail;
+use std::rc::Rc;
+
+fn single_arg(args: &List<Expression>) -> anyhow::Result<&Expression> {
+    match args.iter().collect::<Vec<_>>().as_slice() {
+        [form] => Ok(form),
+        _ => bail!("Expected exactly one argument, got: {}", args),
+    }
+}
+
+fn macroexpand(form: &Expression, env: &Env) -> anyhow::Result<Expression> {
+    let mut form = form.clone();
+
+    while let Some(expansion) = iamlisp_macroexpand_1(&form, env)? {
+        form = expansion;
+    }
+
+    Ok(form)
+}
+
+fn macroexpand_all(form: &Expression, env: &Env) -> anyhow::Result<Expression> {
+    match macroexpand(form, env)? {
+        Expression::List(list) => macroexpand_all_list(&list, env),
+        Expression::Value(Value::Vector(items)) => Ok(Value::Vector(
+            items
+                .iter()
+                .map(|item| macroexpand_all(item, env))
+                .collect::<anyhow::Result<_>>()?,
+        )
+        .into()),
+        Expression::Value(Value::Set(items)) => Ok(Value::Set(
+            items
+                .iter()
+                .map(|item| macroexpand_all(item, env))
+                .collect::<anyhow::Result<_>>()?,
+        )
+        .into()),
+        Expression::Value(Value::Map(entries)) => Ok(Value::Map(
+            entries
+                .iter()
+                .map(|(key, value)| Ok((macroexpand_all(key, env)?, macroexpand_all(value, env)?)))
+                .collect::<anyhow::Result<_>>()?,
+        )
+        .into()),
+        form => Ok(form),
+    }
+}
+
+/// Expands the items of the list from `start`, the items before it are kept as is.
+fn macroexpand_all_from(
+    items: &[&Expression],
+    start: usize,
+    env: &Env,
+) -> anyhow::Result<List<Expression>> {
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            if i < start {
+                Ok((*item).clone())
+            } else {
+                macroexpand_all(item, env)
+            }
+        })
+        .collect()
+}
+
+/// Expands values of `name value` pairs, like definitions of `def` and `loop`.
+fn macroexpand_all_pairs(
+    items: &[&Expression],
+    start: usize,
+    env: &Env,
+) -> anyhow::Result<List<Expression>> {
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            if i < start || (i - start).is_multiple_of(2) {
+                Ok((*item).clone())
+            } else {
+                macroexpand_all(item, env)
+            }
+        })
+        .collect()
+}
+
+/// Parts of special forms which are not evaluated, like names bound by `lambda`, `def`
+/// or `catch`, are kept as is.
+fn macroexpand_all_list(list: &Rc<List<Expression>>, env: &Env) -> anyhow::Result<Expression> {
+    let items = list.iter().collect::<Vec<_>>();
+
+    let expanded = match items.as_slice() {
+        [quote_symbol!(), ..] => return Ok(Expression::List(list.clone())),
+        [quasiquote_symbol!(), template] => {
+            list![
+                quasiquote_symbol!(),
+                macroexpand_template(template, 1, env)?
+            ]
+        }
+        [Expression::Symbol("lambda" | "macro"), ..] => macroexpand_all_from(&items, 2, env)?,
+        [Expression::Symbol("defun" | "defmacro"), ..] => macroexpand_all_from(&items, 3, env)?,
+        [def_symbol!(), ..] => macroexpand_all_pairs(&items, 1, env)?,
+        [loop_symbol!(), Expression::List(bindings), body @ ..] => {
+            let bindings = macroexpand_all_pairs(&bindings.iter().collect::<Vec<_>>(), 0, env)?;
+            let body = macroexpand_all_from(body, 0, env)?;
+
+            List::cons(loop_symbol!(), List::cons(bindings.into(), body))
+        }
+        [try_symbol!(), ..] => items
+            .iter()
+            .map(|item| match item {
+                Expression::List(clause) => match clause.iter().collect::<Vec<_>>().as_slice() {
+                    clause @ [Expression::Symbol("catch"), ..] => {
+                        Ok(macroexpand_all_from(clause, 2, env)?.into())
+                    }
+                    clause @ [Expression::Symbol("finally"), ..] => {
+                        Ok(macroexpand_all_from(clause, 1, env)?.into())
+                    }
+                    _ => macroexpand_all(item, env),
+                },
+                item => macroexpand_all(item, env),
+            })
+            .collect::<anyhow::Result<_>>()?,
+        _ => macroexpand_all_from(&items, 0, env)?,
+    };
+
+    Ok(expanded.into())
+}
+
+/// Expands only forms of `unquote` and `unquote-splicing` of the template, which
+/// are evaluated. Nested templates are expanded at their own depth.
+fn macroexpand_template(
+    template: &Expression,
+    depth: usize,
+    env: &Env,
+) -> anyhow::Result<Expression> {
+    match template {
+        Expression::List(list) => match list.iter().collect::<Vec<_>>().as_slice() {
+            [head @ (unquote_symbol!() | unquote_splicing_symbol!()), form] if depth == 1 => {
+                Ok(list![(*head).clone(), macroexpand_all(form, env)?].into())
+            }
+            [head @ (unquote_symbol!() | unquote_splicing_symbol!()), form] => {
+                Ok(list![(*head).clone(), macroexpand_template(form, depth - 1, env)?].into())
+            }
+            [quasiquote_symbol!(), form] => Ok(list![
+                quasiquote_symbol!(),
+                macroexpand_template(form, depth + 1, env)?
+            ]
+            .into()),
+            items => Ok(items
+                .iter()
+                .map(|item| macroexpand_template(item, depth, env))
+                .collect::<anyhow::Result<List<_>>>()?
+                .into()),
+        },
+        Expression::Value(Value::Vector(items)) => Ok(Value::Vector(
+            items
+                .iter()
+                .map(|item| macroexpand_template(item, depth, env))
+                .collect::<anyhow::Result<_>>()?,
+        )
+        .into()),
+        template => Ok(template.clone()),
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct MacroExpand1;
+
+impl Op for MacroExpand1 {
+    fn name(&self) -> &'static str {
+        "macroexpand-1"
+    }
+
+    fn apply(&self, args: &List<Expression>, env: &Env) -> anyhow::Result<Expression> {
+        let form = single_arg(args)?;
+
+        Ok(iamlisp_macroexpand_1(form, env)?.unwrap_or_else(|| form.clone()))
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct MacroExpand;
+
+impl Op for MacroExpand {
+    fn name(&self) -> &'static str {
+        "macroexpand"
+    }
+
+    fn apply(&self, args: &List<Expression>, env: &Env) -> anyhow::Result<Expression> {
+        macroexpand(single_arg(args)?, env)
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct MacroExpandAll;
+
+impl Op for MacroExpandAll {
+    fn name(&self) -> &'static str {
+        "macroexpand-all"
+    }
+
+    fn apply(&self, args: &List<Expression>, env: &Env) -> anyhow::Result<Expression> {
+        macroexpand_all(single_arg(args)?, env)
+    }
+}
@@ -0,0 +1,317 @@
+use crate::data::{HashMap, List, Vector};
+use crate::eval::env::Env;
+use crate::eval::eval::iamlisp_eval_native_result;
+use crate::eval::native_calls::{NativeResult, Op};
+use crate::eval::types::{Expression, Value};
+use anyhow::bail;
+
+/// Map entries are represented by two-item vectors `[key value]`.
+pub(crate) fn entry_to_pair(key: &Expression, value: &Expression) -> Expression {
+    Value::Vector(Vector::from_iter([key.clone(), value.clone()])).into()
+}
+
+pub(crate) fn pair_to_entry(pair: &Expression) -> anyhow::Result<(Expression, Expression)> {
+    match pair {
+        Expression::Value(Value::Vector(items)) if items.len() == 2 => {
+            Ok((items[0].clone(), items[1].clone()))
+        }
+        _ => bail!("Map entry should be a pair of key and value: {}", pair),
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct MapConstructor;
+
+impl Op for MapConstructor {
+    fn name(&self) -> &'static str {
+        "hash-map"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        if args.len() % 2 != 0 {
+            bail!("Expected pairs of keys and values: {}", args);
+        }
+
+        let mut args = args.iter();
+        let mut entries = HashMap::default();
+
+        while let (Some(key), Some(value)) = (args.next(), args.next()) {
+            entries.insert(key.clone(), value.clone());
+        }
+
+        Ok(Value::Map(entries).into())
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct Get;
+
+impl Op for Get {
+    fn name(&self) -> &'static str {
+        "get"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        let (collection, key, default) = match args.iter().collect::<Vec<_>>().as_slice() {
+            [collection, key] => (*collection, *key, Expression::default()),
+            [collection, key, default] => (*collection, *key, (*default).clone()),
+            _ => bail!("Expected collection, key and optional default: {}", args),
+        };
+
+        let value = match (collection, key) {
+            (Expression::Value(Value::Map(entries)), key) => entries.get(key).cloned(),
+            (Expression::Value(Value::Vector(items)), Expression::Value(Value::Int64(index))) => {
+                usize::try_from(*index)
+                    .ok()
+                    .and_then(|index| items.get(index).cloned())
+            }
+            (Expression::Value(Value::Nil), _) => None,
+            _ => bail!(
+                "Function not implemented for this kind of arguments: {}",
+                args
+            ),
+        };
+
+        Ok(value.unwrap_or(default))
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct Has;
+
+impl Op for Has {
+    fn name(&self) -> &'static str {
+        "has"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        match args.iter().collect::<Vec<_>>().as_slice() {
+            [Expression::Value(Value::Map(entries)), key] => {
+                Ok(Value::Bool(entries.contains_key(*key)).into())
+            }
+            [Expression::Value(Value::Set(items)), item] => {
+                Ok(Value::Bool(items.contains(*item)).into())
+            }
+            _ => bail!(
+                "Function not implemented for this kind of arguments: {}",
+                args
+            ),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct Dissoc;
+
+impl Op for Dissoc {
+    fn name(&self) -> &'static str {
+        "dissoc"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        match args.head() {
+            Some(Expression::Value(Value::Map(entries))) => {
+                let mut entries = entries.clone();
+
+                for key in args.tail().iter() {
+                    entries.remove(key);
+                }
+
+                Ok(Value::Map(entries).into())
+            }
+            _ => bail!(
+                "Function not implemented for this kind of arguments: {}",
+                args
+            ),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct Keys;
+
+impl Op for Keys {
+    fn name(&self) -> &'static str {
+        "keys"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        match args.iter().collect::<Vec<_>>().as_slice() {
+            [Expression::Value(Value::Map(entries))] => {
+                Ok(entries.keys().cloned().collect::<List<_>>().into())
+            }
+            _ => bail!(
+                "Function not implemented for this kind of arguments: {}",
+                args
+            ),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct Vals;
+
+impl Op for Vals {
+    fn name(&self) -> &'static str {
+        "vals"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        match args.iter().collect::<Vec<_>>().as_slice() {
+            [Expression::Value(Value::Map(entries))] => {
+                Ok(entries.values().cloned().collect::<List<_>>().into())
+            }
+            _ => bail!(
+                "Function not implemented for this kind of arguments: {}",
+                args
+            ),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct Entries;
+
+impl Op for Entries {
+    fn name(&self) -> &'static str {
+        "entries"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        match args.iter().collect::<Vec<_>>().as_slice() {
+            [Expression::Value(Value::Map(entries))] => Ok(entries
+                .iter()
+                .map(|(key, value)| entry_to_pair(key, value))
+                .collect::<List<_>>()
+                .into()),
+            _ => bail!(
+                "Function not implemented for this kind of arguments: {}",
+                args
+            ),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct Merge;
+
+impl Op for Merge {
+    fn name(&self) -> &'static str {
+        "merge"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        let mut merged = HashMap::default();
+
+        for arg in args.iter() {
+            match arg {
+                Expression::Value(Value::Map(entries)) => {
+                    merged = entries.clone().union(merged);
+                }
+                Expression::Value(Value::Nil) => (),
+                _ => bail!(
+                    "Function not implemented for this kind of arguments: {}",
+                    args
+                ),
+            }
+        }
+
+        Ok(Value::Map(merged).into())
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct Update;
+
+impl Op for Update {
+    fn name(&self) -> &'static str {
+        "update"
+    }
+
+    fn apply(&self, args: &List<Expression>, env: &Env) -> anyhow::Result<Expression> {
+        iamlisp_eval_native_result(self.apply_with_callbacks(args, env)?, env)
+    }
+
+    fn apply_with_callbacks(
+        &self,
+        args: &List<Expression>,
+        _env: &Env,
+    ) -> anyhow::Result<NativeResult> {
+        let (entries, key, func, extra_args) = match args.iter().collect::<Vec<_>>().as_slice() {
+            [Expression::Value(Value::Map(entries)), key, func, extra_args @ ..] => (
+                entries.clone(),
+                (*key).clone(),
+                (*func).clone(),
+                extra_args.to_vec(),
+            ),
+            _ => bail!(
+                "Expected map, key, function and optional arguments: {}",
+                args
+            ),
+        };
+
+        let current = entries.get(&key).cloned().unwrap_or_default();
+        let func_args = std::iter::once(current)
+            .chain(extra_args.into_iter().cloned())
+            .collect();
+
+        Ok(NativeResult::call(func, func_args, move |updated| {
+            Ok(NativeResult::Return(
+                Value::Map(entries.update(key, updated)).into(),
+            ))
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::list;
+
+    fn map(entries: &[(&str, i64)]) -> Expression {
+        Value::Map(
+            entries
+                .iter()
+                .map(|(key, value)| {
+                    (
+                        Value::Keyword(key.to_string()).into(),
+                        Value::Int64(*value).into(),
+                    )
+                })
+                .collect(),
+        )
+        .into()
+    }
+
+    #[test]
+    fn merge_prefers_later_maps() {
+        let env = Env::new();
+
+        assert_eq!(
+            map(&[("a", 1), ("b", 3), ("c", 4)]),
+            Merge
+                .apply(
+                    &list![map(&[("a", 1), ("b", 2)]), map(&[("b", 3), ("c", 4)])],
+                    &env
+                )
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn dissoc_does_not_change_original() {
+        let env = Env::new();
+        let original = map(&[("a", 1), ("b", 2)]);
+
+        assert_eq!(
+            map(&[("b", 2)]),
+            Dissoc
+                .apply(
+                    &list![original.clone(), Value::Keyword("a".to_string()).into()],
+                    &env
+                )
+                .unwrap()
+        );
+        assert_eq!(map(&[("a", 1), ("b", 2)]), original);
+    }
+}
@@ -1,8 +1,257 @@
 use crate::data::List;
+use crate::eval::convert::TypedOp;
 use crate::eval::env::Env;
 use crate::eval::native_calls::Op;
 use crate::eval::types::{Expression, Value};
 use anyhow::bail;
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{Signed, ToPrimitive, Zero};
+use std::cmp::Ordering;
+
+/// Upper bound of the size of exact `pow` results, so a large exponent fails
+/// instead of allocating gigabytes.
+const MAX_POW_BITS: u64 = 1 << 24;
+
+/*
+ Numeric coercion:
+
+ Arguments of arithmetic are promoted to the widest type among all of them,
+ `Int64` < `BigInt` < `Ratio` < `Float64`, before the operation. So any float
+ argument makes the result a float, whatever its position: `(+ 1 2.5)` and
+ `(+ 2.5 1)` are both `3.5`. Floats are never truncated to integers.
+
+ Operations on integers and ratios are exact: `(/ 7 2)` is the ratio `7/2`, and
+ when an integer result doesn't fit into `Int64`, it is computed as `BigInt`
+ instead. Results are demoted to the narrowest exact type, `(/ 4 2)` is the
+ integer `2` and `BigInt` results that fit are `Int64`, so each number has
+ exactly one representation.
+*/
+
+/// Argument of numeric functions.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Number {
+    Int(i64),
+    Big(BigInt),
+    Ratio(BigRational),
+    Float(f64),
+}
+
+impl Number {
+    pub(crate) fn from_expression(expression: &Expression) -> Option<Number> {
+        match expression {
+            Expression::Value(Value::Int64(int)) => Some(Number::Int(*int)),
+            Expression::Value(Value::BigInt(big)) => Some(Number::Big(big.clone())),
+            Expression::Value(Value::Ratio(ratio)) => Some(Number::Ratio(ratio.clone())),
+            Expression::Value(Value::Float64(float)) => Some(Number::Float(*float)),
+            _ => None,
+        }
+    }
+
+    fn from_arg(arg: &Expression, args: &List<Expression>) -> anyhow::Result<Number> {
+        match Number::from_expression(arg) {
+            Some(number) => Ok(number),
+            None => bail!(
+                "Function not implemented for this kind of arguments: {}",
+                args
+            ),
+        }
+    }
+
+    /// Integer of any size, as `Int` if it fits.
+    pub(crate) fn integer(big: BigInt) -> Number {
+        match big.to_i64() {
+            Some(int) => Number::Int(int),
+            None => Number::Big(big),
+        }
+    }
+
+    /// Ratio with denominator `1` is an integer.
+    pub(crate) fn rational(ratio: BigRational) -> Number {
+        if ratio.is_integer() {
+            Number::integer(ratio.to_integer())
+        } else {
+            Number::Ratio(ratio)
+        }
+    }
+
+    fn is_float(&self) -> bool {
+        matches!(self, Number::Float(_))
+    }
+
+    fn as_float(&self) -> f64 {
+        match self {
+            Number::Int(int) => *int as f64,
+            Number::Big(big) => big.to_f64().unwrap_or(f64::NAN),
+            Number::Ratio(ratio) => ratio.to_f64().unwrap_or(f64::NAN),
+            Number::Float(float) => *float,
+        }
+    }
+
+    /// Floats are converted exactly, but exact operations only get integers
+    /// and ratios.
+    fn as_ratio(&self) -> BigRational {
+        match self {
+            Number::Int(int) => BigRational::from_integer((*int).into()),
+            Number::Big(big) => BigRational::from_integer(big.clone()),
+            Number::Ratio(ratio) => ratio.clone(),
+            Number::Float(float) => BigRational::from_float(*float).unwrap_or_default(),
+        }
+    }
+
+    /// Order by value across types. `None` if one of the numbers is `NaN`.
+    pub(crate) fn compare(&self, other: &Number) -> Option<Ordering> {
+        match (self, other) {
+            (Number::Int(a), Number::Int(b)) => Some(a.cmp(b)),
+            (Number::Float(a), Number::Float(b)) => a.partial_cmp(b),
+            (Number::Float(a), b) => Number::compare_float(*a, b),
+            (a, Number::Float(b)) => Number::compare_float(*b, a).map(Ordering::reverse),
+            (a, b) => Some(a.as_ratio().cmp(&b.as_ratio())),
+        }
+    }
+
+    /// Finite floats are exact fractions, so they are compared with integers
+    /// and ratios without rounding those to floats.
+    fn compare_float(float: f64, exact: &Number) -> Option<Ordering> {
+        const EXACT_FLOAT_INT: u64 = 1 << f64::MANTISSA_DIGITS;
+
+        match exact {
+            _ if float.is_nan() => None,
+            _ if float.is_infinite() => Some(if float > 0.0 {
+                Ordering::Greater
+            } else {
+                Ordering::Less
+            }),
+            Number::Int(int) if int.unsigned_abs() <= EXACT_FLOAT_INT => {
+                float.partial_cmp(&(*int as f64))
+            }
+            exact => Some(Number::Float(float).as_ratio().cmp(&exact.as_ratio())),
+        }
+    }
+}
+
+impl From<Number> for Expression {
+    fn from(number: Number) -> Self {
+        match number {
+            Number::Int(int) => Value::Int64(int).into(),
+            Number::Big(big) => Value::BigInt(big).into(),
+            Number::Ratio(ratio) => Value::Ratio(ratio).into(),
+            Number::Float(float) => Value::Float64(float).into(),
+        }
+    }
+}
+
+fn numbers<const N: usize>(args: &List<Expression>) -> anyhow::Result<[Number; N]> {
+    if args.len() != N as i64 {
+        bail!(
+            "expected {} argument{}, got {}",
+            N,
+            if N == 1 { "" } else { "s" },
+            args.len()
+        );
+    }
+
+    let numbers = args
+        .iter()
+        .map(|arg| Number::from_arg(arg, args))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    Ok(numbers.try_into().expect("length is checked above"))
+}
+
+/// Numbers of all arguments. If any of them is a float, all of them are.
+fn promoted(args: &List<Expression>) -> anyhow::Result<Vec<Number>> {
+    let numbers = args
+        .iter()
+        .map(|arg| Number::from_arg(arg, args))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    if numbers.iter().any(Number::is_float) {
+        Ok(numbers
+            .iter()
+            .map(|n| Number::Float(n.as_float()))
+            .collect())
+    } else {
+        Ok(numbers)
+    }
+}
+
+/// Folds promoted numbers, starting from `init` or, without it, from the
+/// first number. `int_op` returns `None` if the result is not an `Int64`, and
+/// then `exact_op` computes it.
+fn fold_numbers(
+    args: &List<Expression>,
+    init: Option<i64>,
+    int_op: impl Fn(i64, i64) -> Option<i64>,
+    exact_op: impl Fn(BigRational, BigRational) -> anyhow::Result<BigRational>,
+    float_op: impl Fn(f64, f64) -> f64,
+) -> anyhow::Result<Expression> {
+    let numbers = promoted(args)?;
+    let is_float = numbers.first().is_some_and(Number::is_float);
+    let mut numbers = numbers.into_iter();
+
+    let first = match init {
+        Some(init) if is_float => Number::Float(init as f64),
+        Some(init) => Number::Int(init),
+        None => match numbers.next() {
+            Some(first) => first,
+            None => bail!("expected at least 1 argument, got 0"),
+        },
+    };
+
+    Ok(numbers
+        .try_fold(first, |acc, number| match (acc, number) {
+            (Number::Int(a), Number::Int(b)) if int_op(a, b).is_some() => {
+                Ok(Number::Int(int_op(a, b).unwrap_or_default()))
+            }
+            (a, b) if a.is_float() || b.is_float() => {
+                Ok(Number::Float(float_op(a.as_float(), b.as_float())))
+            }
+            (a, b) => exact_op(a.as_ratio(), b.as_ratio()).map(Number::rational),
+        })?
+        .into())
+}
+
+/// Applies `int_op` to an integer argument, or `exact_op` if the result is not
+/// an `Int64`, or `float_op` to a float one.
+fn unary(
+    args: &List<Expression>,
+    int_op: impl Fn(i64) -> Option<i64>,
+    exact_op: impl Fn(BigRational) -> BigRational,
+    float_op: impl Fn(f64) -> f64,
+) -> anyhow::Result<Expression> {
+    let [number] = numbers(args)?;
+
+    Ok(match number {
+        Number::Int(int) if int_op(int).is_some() => Number::Int(int_op(int).unwrap_or_default()),
+        Number::Float(float) => Number::Float(float_op(float)),
+        number => Number::rational(exact_op(number.as_ratio())),
+    }
+    .into())
+}
+
+/// Applies `exact_op` to the quotient of two numbers, or `float_op` if any of
+/// them is a float.
+fn quotient(
+    args: &List<Expression>,
+    exact_op: impl Fn(&BigRational) -> BigRational,
+    float_op: impl Fn(f64) -> f64,
+) -> anyhow::Result<Expression> {
+    let [a, b] = numbers(args)?;
+
+    if a.is_float() || b.is_float() {
+        return Ok(Value::Float64(float_op(a.as_float() / b.as_float())).into());
+    }
+
+    let divisor = b.as_ratio();
+
+    if divisor.is_zero() {
+        bail!("division by zero");
+    }
+
+    Ok(Number::rational(exact_op(&(a.as_ratio() / divisor))).into())
+}
 
 #[derive(Clone, PartialEq)]
 pub(crate) struct Multiply;
@@ -13,31 +262,17 @@ impl Op for Multiply {
     }
 
     fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
-        Ok(match args.head() {
-            Some(Expression::Value(Value::Int64(_))) => {
-                Value::Int64(args.iter().try_fold(1, |acc, exp| match exp {
-                    Expression::Value(Value::Int64(val)) => Ok(acc * *val),
-                    Expression::Value(Value::Float64(val)) => Ok(acc * (*val as i64)),
-                    x => bail!("Unable to multiply {} by {}", acc, x),
-                })?)
-                .into()
-            }
-            Some(Expression::Value(Value::Float64(_))) => {
-                Value::Float64(args.iter().try_fold(1.0, |acc, exp| match exp {
-                    Expression::Value(Value::Int64(val)) => Ok(acc * (*val as f64)),
-                    Expression::Value(Value::Float64(val)) => Ok(acc * *val),
-                    x => bail!("Unable to multiply {} by {}", acc, x),
-                })?)
-                .into()
-            }
-            _ => bail!(
-                "Function not implemented for this kind of arguments: {}",
-                args
-            ),
-        })
+        fold_numbers(
+            args,
+            Some(1),
+            i64::checked_mul,
+            |acc, val| Ok(acc * val),
+            |acc, val| acc * val,
+        )
     }
 }
 
+/// Division of integers is exact, `(/ 1 3)` is the ratio `1/3`.
 #[derive(Clone, PartialEq)]
 pub(crate) struct Divide;
 
@@ -47,30 +282,36 @@ impl Op for Divide {
     }
 
     fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
-        Ok(match args.head() {
-            Some(Expression::Value(Value::Int64(init))) => {
-                Value::Int64(args.tail().iter().try_fold(*init, |acc, exp| match exp {
-                    Expression::Value(Value::Int64(val)) => Ok(acc / *val),
-                    Expression::Value(Value::Float64(val)) => Ok(acc / (*val as i64)),
-                    x => bail!("Unable to divide {} by {}", acc, x),
-                })?)
-                .into()
-            }
+        fold_numbers(
+            args,
+            None,
+            |acc, val| match acc.checked_rem(val) {
+                Some(0) => acc.checked_div(val),
+                _ => None,
+            },
+            |acc, val| {
+                if val.is_zero() {
+                    bail!("division by zero");
+                }
 
-            Some(Expression::Value(Value::Float64(init))) => {
-                Value::Float64(args.tail().iter().try_fold(*init, |acc, exp| match exp {
-                    Expression::Value(Value::Int64(val)) => Ok(acc / (*val as f64)),
-                    Expression::Value(Value::Float64(val)) => Ok(acc / *val),
-                    x => bail!("Unable to divide {} by {}", acc, x),
-                })?)
-                .into()
-            }
+                Ok(acc / val)
+            },
+            |acc, val| acc / val,
+        )
+    }
+}
 
-            _ => bail!(
-                "Function not implemented for this kind of arguments: {}",
-                args
-            ),
-        })
+/// `(quot a b)` rounds the quotient towards zero.
+#[derive(Clone, PartialEq)]
+pub(crate) struct Quotient;
+
+impl Op for Quotient {
+    fn name(&self) -> &'static str {
+        "quot"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        quotient(args, BigRational::trunc, f64::trunc)
     }
 }
 
@@ -83,25 +324,64 @@ impl Op for Pow {
     }
 
     fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
-        Ok(match (args.head(), args.tail().head()) {
-            (
-                Some(Expression::Value(Value::Int64(base))),
-                Some(Expression::Value(Value::Int64(power))),
-            ) => Value::Int64(base.pow(*power as u32)).into(),
+        Ok(match numbers(args)? {
+            [base, power @ (Number::Float(_) | Number::Ratio(_))]
+            | [base @ Number::Float(_), power] => {
+                Value::Float64(base.as_float().powf(power.as_float())).into()
+            }
+            // Negative powers are fractions, and they are floats like roots.
+            [base, power] if power.as_ratio().is_negative() => {
+                Value::Float64(base.as_float().powf(power.as_float())).into()
+            }
+            [base, power] => {
+                let Some(power) = power.as_ratio().to_integer().to_i32() else {
+                    bail!("Exponent is too large: {}", Expression::from(power));
+                };
 
-            (
-                Some(Expression::Value(Value::Float64(base))),
-                Some(Expression::Value(Value::Float64(power))),
-            ) => Value::Float64(base.powf(*power)).into(),
+                let ratio = base.as_ratio();
+                let bits = ratio.numer().bits().max(ratio.denom().bits());
+                // Powers of 0, 1 and -1 don't grow, other bases need `bits` per step at most.
+                if ratio.numer().abs() != *ratio.denom()
+                    && !ratio.is_zero()
+                    && bits.saturating_mul(power as u64) > MAX_POW_BITS
+                {
+                    bail!(
+                        "Result of pow is too large: {} ^ {}",
+                        Expression::from(base.clone()),
+                        power
+                    );
+                }
 
-            _ => bail!(
-                "Function not implemented for this kind of arguments: {}",
-                args
-            ),
+                match base {
+                    Number::Int(int) => match int.checked_pow(power as u32) {
+                        Some(result) => Value::Int64(result).into(),
+                        None => Number::integer(BigInt::from(int).pow(power as u32)).into(),
+                    },
+                    base => Number::rational(base.as_ratio().pow(power)).into(),
+                }
+            }
         })
     }
 }
 
+#[derive(Clone, PartialEq)]
+pub(crate) struct Decrement;
+
+impl Op for Decrement {
+    fn name(&self) -> &'static str {
+        "dec"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        unary(
+            args,
+            |int| int.checked_sub(1),
+            |ratio| ratio - BigInt::from(1),
+            |float| float - 1.0,
+        )
+    }
+}
+
 #[derive(Clone, PartialEq)]
 pub(crate) struct Subtract;
 
@@ -111,34 +391,17 @@ impl Op for Subtract {
     }
 
     fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
-        Ok(match args.head() {
-            Some(Expression::Value(Value::Int64(init))) if args.tail().is_empty() => {
-                Value::Int64(-*init).into()
-            }
-            Some(Expression::Value(Value::Float64(init))) if args.tail().is_empty() => {
-                Value::Float64(-*init).into()
-            }
-            Some(Expression::Value(Value::Int64(init))) => {
-                Value::Int64(args.tail().iter().try_fold(*init, |acc, exp| match exp {
-                    Expression::Value(Value::Int64(val)) => Ok(acc - *val),
-                    Expression::Value(Value::Float64(val)) => Ok(acc - (*val as i64)),
-                    x => bail!("Unable to subtract {} and {}", acc, x),
-                })?)
-                .into()
-            }
-            Some(Expression::Value(Value::Float64(init))) => {
-                Value::Float64(args.tail().iter().try_fold(*init, |acc, exp| match exp {
-                    Expression::Value(Value::Int64(val)) => Ok(acc - (*val as f64)),
-                    Expression::Value(Value::Float64(val)) => Ok(acc - *val),
-                    x => bail!("Unable to subtract {} and {}", acc, x),
-                })?)
-                .into()
-            }
-            _ => bail!(
-                "Function not implemented for this kind of arguments: {}",
-                args
-            ),
-        })
+        if args.len() == 1 {
+            return unary(args, i64::checked_neg, |ratio| -ratio, |float| -float);
+        }
+
+        fold_numbers(
+            args,
+            None,
+            i64::checked_sub,
+            |acc, val| Ok(acc - val),
+            |acc, val| acc - val,
+        )
     }
 }
 
@@ -151,27 +414,245 @@ impl Op for Sum {
     }
 
     fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
-        Ok(match args.head() {
-            Some(Expression::Value(Value::Int64(_))) => {
-                Value::Int64(args.iter().try_fold(0, |acc, exp| match exp {
-                    Expression::Value(Value::Int64(val)) => Ok(acc + *val),
-                    Expression::Value(Value::Float64(val)) => Ok(acc + (*val as i64)),
-                    x => bail!("Unable to sum {} and {}", acc, x),
-                })?)
-                .into()
+        fold_numbers(
+            args,
+            Some(0),
+            i64::checked_add,
+            |acc, val| Ok(acc + val),
+            |acc, val| acc + val,
+        )
+    }
+}
+
+/// `(// a b)` rounds the quotient towards negative infinity.
+#[derive(Clone, PartialEq)]
+pub(crate) struct FloorDivide;
+
+impl Op for FloorDivide {
+    fn name(&self) -> &'static str {
+        "//"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        if let [Number::Int(a), Number::Int(b)] = numbers(args)? {
+            if b != 0 && a.checked_div(b).is_some() {
+                return Ok(Value::Int64(Integer::div_floor(&a, &b)).into());
             }
-            Some(Expression::Value(Value::Float64(_))) => {
-                Value::Float64(args.iter().try_fold(0.0, |acc, exp| match exp {
-                    Expression::Value(Value::Int64(val)) => Ok(acc + (*val as f64)),
-                    Expression::Value(Value::Float64(val)) => Ok(acc + *val),
-                    x => bail!("Unable to sum {} and {}", acc, x),
-                })?)
-                .into()
+        }
+
+        quotient(args, BigRational::floor, f64::floor)
+    }
+}
+
+/// `(% a b)` has the sign of the dividend, like `rem` in Rust.
+#[derive(Clone, PartialEq)]
+pub(crate) struct Remainder;
+
+impl Op for Remainder {
+    fn name(&self) -> &'static str {
+        "%"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        let [a, b] = numbers(args)?;
+
+        if a.is_float() || b.is_float() {
+            return Ok(Value::Float64(a.as_float() % b.as_float()).into());
+        }
+
+        Ok(match (a, b) {
+            (_, Number::Int(0)) => bail!("division by zero"),
+            (Number::Int(a), Number::Int(b)) => Value::Int64(a.wrapping_rem(b)).into(),
+            (a, b) => Number::rational(a.as_ratio() % b.as_ratio()).into(),
+        })
+    }
+}
+
+/// `(mod a b)` has the sign of the divisor, so `(mod -1 3)` is `2`.
+#[derive(Clone, PartialEq)]
+pub(crate) struct Modulo;
+
+impl Op for Modulo {
+    fn name(&self) -> &'static str {
+        "mod"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        let [a, b] = numbers(args)?;
+
+        if a.is_float() || b.is_float() {
+            let (a, b) = (a.as_float(), b.as_float());
+            let remainder = a % b;
+
+            if remainder != 0.0 && (remainder < 0.0) != (b < 0.0) {
+                return Ok(Value::Float64(remainder + b).into());
+            }
+
+            return Ok(Value::Float64(remainder).into());
+        }
+
+        Ok(match (a, b) {
+            (_, Number::Int(0)) => bail!("division by zero"),
+            (Number::Int(a), Number::Int(b)) => {
+                let remainder = a.wrapping_rem(b);
+
+                if remainder != 0 && (remainder < 0) != (b < 0) {
+                    Value::Int64(remainder + b).into()
+                } else {
+                    Value::Int64(remainder).into()
+                }
+            }
+            (a, b) => {
+                let (a, b) = (a.as_ratio(), b.as_ratio());
+                let quotient = (&a / &b).floor();
+
+                Number::rational(a - b * quotient).into()
             }
-            _ => bail!(
-                "Function not implemented for this kind of arguments: {}",
-                args
-            ),
         })
     }
 }
+
+/// Keeps the number for which `keep(ordering to the kept one)` holds. If any
+/// argument is a float, the result is a float, so `(max 3 2.5)` is `3.0`.
+fn extremum(args: &List<Expression>, keep: fn(Ordering) -> bool) -> anyhow::Result<Expression> {
+    let numbers = args
+        .iter()
+        .map(|arg| Number::from_arg(arg, args))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let Some((first, rest)) = numbers.split_first() else {
+        bail!("expected at least 1 argument, got 0");
+    };
+
+    let mut result = first;
+
+    for number in rest {
+        match number.compare(result) {
+            Some(ordering) if keep(ordering) => result = number,
+            Some(_) => (),
+            None => return Ok(Value::Float64(f64::NAN).into()),
+        }
+    }
+
+    if numbers.iter().any(Number::is_float) {
+        return Ok(Value::Float64(result.as_float()).into());
+    }
+
+    Ok(result.clone().into())
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct Min;
+
+impl Op for Min {
+    fn name(&self) -> &'static str {
+        "min"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        extremum(args, Ordering::is_lt)
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct Max;
+
+impl Op for Max {
+    fn name(&self) -> &'static str {
+        "max"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        extremum(args, Ordering::is_gt)
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct Abs;
+
+impl Op for Abs {
+    fn name(&self) -> &'static str {
+        "abs"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        unary(args, i64::checked_abs, |ratio| ratio.abs(), f64::abs)
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct Increment;
+
+impl Op for Increment {
+    fn name(&self) -> &'static str {
+        "inc"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        unary(
+            args,
+            |int| int.checked_add(1),
+            |ratio| ratio + BigInt::from(1),
+            |float| float + 1.0,
+        )
+    }
+}
+
+/// `floor`, `ceil` and `round` keep integers as they are, and turn ratios into
+/// integers.
+#[derive(Clone)]
+pub(crate) struct Rounding(
+    pub(crate) &'static str,
+    pub(crate) fn(&BigRational) -> BigRational,
+    pub(crate) fn(f64) -> f64,
+);
+
+impl Op for Rounding {
+    fn name(&self) -> &'static str {
+        self.0
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        unary(args, Some, |ratio| self.1(&ratio), self.2)
+    }
+}
+
+/// `(numerator 2/3)` is `2`. Integers are their own numerators.
+#[derive(Clone, PartialEq)]
+pub(crate) struct Numerator;
+
+impl Op for Numerator {
+    fn name(&self) -> &'static str {
+        "numerator"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        match numbers(args)? {
+            [Number::Float(_)] => bail!("Numerator of Float64 is not defined: {}", args),
+            [number] => Ok(Number::integer(number.as_ratio().numer().clone()).into()),
+        }
+    }
+}
+
+/// `(denominator 2/3)` is `3`. Denominators of integers are `1`.
+#[derive(Clone, PartialEq)]
+pub(crate) struct Denominator;
+
+impl Op for Denominator {
+    fn name(&self) -> &'static str {
+        "denominator"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        match numbers(args)? {
+            [Number::Float(_)] => bail!("Denominator of Float64 is not defined: {}", args),
+            [number] => Ok(Number::integer(number.as_ratio().denom().clone()).into()),
+        }
+    }
+}
+
+/// Function of one float, like `sqrt` or `sin`. Integers and ratios are
+/// converted to floats, and the result is always a float.
+pub(crate) fn float_fn(name: &'static str, func: fn(f64) -> f64) -> impl Op {
+    TypedOp::new(name, move |x: f64| Ok(func(x)))
+}
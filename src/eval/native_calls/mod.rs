@@ -1,28 +1,146 @@
 use crate::data::List;
 use crate::eval::env::Env;
+use crate::eval::native_calls::apply::Apply;
 use crate::eval::native_calls::begin::Begin;
 use crate::eval::native_calls::list_constructor::ListConstructor;
 use crate::eval::types::{Expression, NativeCall};
 use anyhow::Result;
-use math::{Divide, Multiply, Pow, Subtract, Sum};
+use doc::Doc;
+use error::{ErrorConstructor, ErrorData, ErrorKind, ErrorMessage, IsError, Throw};
+use higher_order::{Any, Every, Filter, Find, Includes, Map, Reduce};
+use io::Print;
+use list_ops::{
+    Append, Cons, DropItems, First, IntoList, Length, Range, Rest, Reverse, TakeItems, Zip,
+};
+use macroexpand::{MacroExpand, MacroExpand1, MacroExpandAll};
+pub(crate) use map::MapConstructor;
+use map::{Dissoc, Entries, Get, Has, Keys, Merge, Update, Vals};
+use math::{
+    float_fn, Abs, Decrement, Denominator, Divide, FloorDivide, Increment, Max, Min, Modulo,
+    Multiply, Numerator, Pow, Quotient, Remainder, Rounding, Subtract, Sum,
+};
+use num_rational::BigRational;
+pub(crate) use set::SetConstructor;
+use set::{Add, Delete, Difference, Intersection, IntoSet, IsSubset, Union};
 use std::sync::Arc;
+pub(crate) use vector::VectorConstructor;
+use vector::{Assoc, Conj, Count, IntoVector, Nth, Subvec};
 
+mod apply;
 mod begin;
 mod cmp;
+mod doc;
+mod error;
+mod higher_order;
+mod io;
 mod list_constructor;
-mod math;
+mod list_ops;
+mod macroexpand;
+mod map;
+pub(crate) mod math;
+mod set;
+mod vector;
 
-pub(crate) trait Op {
+/// Native call, which gets already evaluated arguments.
+pub trait Op {
     fn name(&self) -> &'static str;
     fn apply(&self, args: &List<Expression>, env: &Env) -> Result<Expression>;
+
+    /// Same as `apply`, but the native call may ask the evaluator to call a
+    /// lambda or another native call and continue with its result. Calls are
+    /// made on the evaluator's stack, so callbacks calling natives calling
+    /// callbacks don't grow the Rust stack.
+    fn apply_with_callbacks(&self, args: &List<Expression>, env: &Env) -> Result<NativeResult> {
+        self.apply(args, env).map(NativeResult::Return)
+    }
+}
+
+/// Rest of a native call, which gets the result of the callback.
+pub type Continuation = Box<dyn FnOnce(Expression) -> Result<NativeResult>>;
+
+/// Result of a native call which may need to call back into iamlisp.
+pub enum NativeResult {
+    Return(Expression),
+    /// Calls `callable` with `args` and passes the result to `then`.
+    Call {
+        callable: Expression,
+        args: List<Expression>,
+        then: Continuation,
+    },
+    /// Unwinds the evaluation up to the nearest `try`, which catches the value.
+    Throw(Expression),
+}
+
+impl NativeResult {
+    pub fn call(
+        callable: Expression,
+        args: List<Expression>,
+        then: impl FnOnce(Expression) -> Result<NativeResult> + 'static,
+    ) -> Self {
+        NativeResult::Call {
+            callable,
+            args,
+            then: Box::new(then),
+        }
+    }
 }
 
 pub(crate) fn load_native_calls(env: &mut Env) {
     env.set("+", NativeCall(Arc::new(Box::from(Sum))).into());
     env.set("-", NativeCall(Arc::new(Box::from(Subtract))).into());
     env.set("/", NativeCall(Arc::new(Box::from(Divide))).into());
+    env.set("quot", NativeCall(Arc::new(Box::from(Quotient))).into());
     env.set("*", NativeCall(Arc::new(Box::from(Multiply))).into());
     env.set("pow", NativeCall(Arc::new(Box::from(Pow))).into());
+    env.set("dec", NativeCall(Arc::new(Box::from(Decrement))).into());
+    env.set("inc", NativeCall(Arc::new(Box::from(Increment))).into());
+    env.set("//", NativeCall(Arc::new(Box::from(FloorDivide))).into());
+    env.set("%", NativeCall(Arc::new(Box::from(Remainder))).into());
+    env.set("mod", NativeCall(Arc::new(Box::from(Modulo))).into());
+    env.set("min", NativeCall(Arc::new(Box::from(Min))).into());
+    env.set("max", NativeCall(Arc::new(Box::from(Max))).into());
+    env.set("abs", NativeCall(Arc::new(Box::from(Abs))).into());
+    env.set(
+        "numerator",
+        NativeCall(Arc::new(Box::from(Numerator))).into(),
+    );
+    env.set(
+        "denominator",
+        NativeCall(Arc::new(Box::from(Denominator))).into(),
+    );
+
+    for (name, exact, float) in [
+        (
+            "floor",
+            BigRational::floor as fn(&BigRational) -> BigRational,
+            f64::floor as fn(f64) -> f64,
+        ),
+        ("ceil", BigRational::ceil, f64::ceil),
+        ("round", BigRational::round, f64::round),
+    ] {
+        env.set(
+            name,
+            NativeCall(Arc::new(Box::from(Rounding(name, exact, float)))).into(),
+        );
+    }
+
+    for (name, func) in [
+        ("float", (|x| x) as fn(f64) -> f64),
+        ("sqrt", f64::sqrt),
+        ("exp", f64::exp),
+        ("log", f64::ln),
+        ("sin", f64::sin),
+        ("cos", f64::cos),
+        ("tan", f64::tan),
+        ("asin", f64::asin),
+        ("acos", f64::acos),
+        ("atan", f64::atan),
+    ] {
+        env.set(
+            name,
+            NativeCall(Arc::new(Box::new(float_fn(name, func)))).into(),
+        );
+    }
 
     env.set("=", NativeCall(Arc::new(Box::from(cmp::Eq))).into());
     env.set("!=", NativeCall(Arc::new(Box::from(cmp::Ne))).into());
@@ -32,8 +150,113 @@ pub(crate) fn load_native_calls(env: &mut Env) {
     env.set("<=", NativeCall(Arc::new(Box::from(cmp::Le))).into());
 
     env.set("begin", NativeCall(Arc::new(Box::from(Begin))).into());
+    env.set("apply", NativeCall(Arc::new(Box::from(Apply))).into());
     env.set(
         "list",
         NativeCall(Arc::new(Box::from(ListConstructor))).into(),
     );
+    env.set("cons", NativeCall(Arc::new(Box::from(Cons))).into());
+    env.set("reverse", NativeCall(Arc::new(Box::from(Reverse))).into());
+    env.set("append", NativeCall(Arc::new(Box::from(Append))).into());
+    env.set("to-list", NativeCall(Arc::new(Box::from(IntoList))).into());
+    env.set("car", NativeCall(Arc::new(Box::from(First("car")))).into());
+    env.set(
+        "first",
+        NativeCall(Arc::new(Box::from(First("first")))).into(),
+    );
+    env.set("cdr", NativeCall(Arc::new(Box::from(Rest("cdr")))).into());
+    env.set("rest", NativeCall(Arc::new(Box::from(Rest("rest")))).into());
+    env.set("length", NativeCall(Arc::new(Box::from(Length))).into());
+    env.set("take", NativeCall(Arc::new(Box::from(TakeItems))).into());
+    env.set("drop", NativeCall(Arc::new(Box::from(DropItems))).into());
+    env.set("range", NativeCall(Arc::new(Box::from(Range))).into());
+    env.set("zip", NativeCall(Arc::new(Box::from(Zip))).into());
+
+    env.set("map", NativeCall(Arc::new(Box::from(Map))).into());
+    env.set("filter", NativeCall(Arc::new(Box::from(Filter))).into());
+    env.set("reduce", NativeCall(Arc::new(Box::from(Reduce))).into());
+    env.set("find", NativeCall(Arc::new(Box::from(Find))).into());
+    env.set("some", NativeCall(Arc::new(Box::from(Any))).into());
+    env.set("every?", NativeCall(Arc::new(Box::from(Every))).into());
+    env.set(
+        "includes?",
+        NativeCall(Arc::new(Box::from(Includes))).into(),
+    );
+
+    env.set("doc", NativeCall(Arc::new(Box::from(Doc))).into());
+    env.set(
+        "macroexpand-1",
+        NativeCall(Arc::new(Box::from(MacroExpand1))).into(),
+    );
+    env.set(
+        "macroexpand",
+        NativeCall(Arc::new(Box::from(MacroExpand))).into(),
+    );
+    env.set(
+        "macroexpand-all",
+        NativeCall(Arc::new(Box::from(MacroExpandAll))).into(),
+    );
+
+    env.set(
+        "vector",
+        NativeCall(Arc::new(Box::from(VectorConstructor))).into(),
+    );
+    env.set("vec", NativeCall(Arc::new(Box::from(IntoVector))).into());
+    env.set("nth", NativeCall(Arc::new(Box::from(Nth))).into());
+    env.set("count", NativeCall(Arc::new(Box::from(Count))).into());
+    env.set("conj", NativeCall(Arc::new(Box::from(Conj))).into());
+    env.set("assoc", NativeCall(Arc::new(Box::from(Assoc))).into());
+    env.set("subvec", NativeCall(Arc::new(Box::from(Subvec))).into());
+
+    env.set(
+        "hash-map",
+        NativeCall(Arc::new(Box::from(MapConstructor))).into(),
+    );
+    env.set("get", NativeCall(Arc::new(Box::from(Get))).into());
+    env.set("has", NativeCall(Arc::new(Box::from(Has))).into());
+    env.set("dissoc", NativeCall(Arc::new(Box::from(Dissoc))).into());
+    env.set("keys", NativeCall(Arc::new(Box::from(Keys))).into());
+    env.set("vals", NativeCall(Arc::new(Box::from(Vals))).into());
+    env.set("entries", NativeCall(Arc::new(Box::from(Entries))).into());
+    env.set("merge", NativeCall(Arc::new(Box::from(Merge))).into());
+    env.set("update", NativeCall(Arc::new(Box::from(Update))).into());
+
+    env.set(
+        "hash-set",
+        NativeCall(Arc::new(Box::from(SetConstructor))).into(),
+    );
+    env.set("set", NativeCall(Arc::new(Box::from(IntoSet))).into());
+    env.set("add", NativeCall(Arc::new(Box::from(Add))).into());
+    env.set("delete", NativeCall(Arc::new(Box::from(Delete))).into());
+    env.set("union", NativeCall(Arc::new(Box::from(Union))).into());
+    env.set(
+        "intersection",
+        NativeCall(Arc::new(Box::from(Intersection))).into(),
+    );
+    env.set(
+        "difference",
+        NativeCall(Arc::new(Box::from(Difference))).into(),
+    );
+    env.set("subset?", NativeCall(Arc::new(Box::from(IsSubset))).into());
+
+    env.set("print", NativeCall(Arc::new(Box::from(Print))).into());
+
+    env.set(
+        "error",
+        NativeCall(Arc::new(Box::from(ErrorConstructor))).into(),
+    );
+    env.set("throw", NativeCall(Arc::new(Box::from(Throw))).into());
+    env.set("error?", NativeCall(Arc::new(Box::from(IsError))).into());
+    env.set(
+        "error-kind",
+        NativeCall(Arc::new(Box::from(ErrorKind))).into(),
+    );
+    env.set(
+        "error-message",
+        NativeCall(Arc::new(Box::from(ErrorMessage))).into(),
+    );
+    env.set(
+        "error-data",
+        NativeCall(Arc::new(Box::from(ErrorData))).into(),
+    );
 }
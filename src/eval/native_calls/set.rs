@@ -0,0 +1,224 @@
+use crate::data::{HashSet, List};
+use crate::eval::env::Env;
+use crate::eval::native_calls::Op;
+use crate::eval::types::{Expression, Value};
+use anyhow::bail;
+
+fn sets(args: &List<Expression>) -> anyhow::Result<Vec<&HashSet<Expression>>> {
+    args.iter()
+        .map(|arg| match arg {
+            Expression::Value(Value::Set(items)) => Ok(items),
+            _ => bail!("Expected sets as arguments, got: {}", args),
+        })
+        .collect()
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct SetConstructor;
+
+impl Op for SetConstructor {
+    fn name(&self) -> &'static str {
+        "hash-set"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        Ok(Value::Set(args.iter().cloned().collect()).into())
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct IntoSet;
+
+impl Op for IntoSet {
+    fn name(&self) -> &'static str {
+        "set"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        let items = match args.iter().collect::<Vec<_>>().as_slice() {
+            [Expression::List(list)] => list.iter().cloned().collect(),
+            [Expression::Value(Value::Vector(items))] => items.iter().cloned().collect(),
+            [Expression::Value(Value::Set(items))] => items.clone(),
+            _ => bail!(
+                "Function not implemented for this kind of arguments: {}",
+                args
+            ),
+        };
+
+        Ok(Value::Set(items).into())
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct Add;
+
+impl Op for Add {
+    fn name(&self) -> &'static str {
+        "add"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        match args.head() {
+            Some(Expression::Value(Value::Set(items))) => {
+                let mut items = items.clone();
+
+                for item in args.tail().iter() {
+                    items.insert(item.clone());
+                }
+
+                Ok(Value::Set(items).into())
+            }
+            _ => bail!(
+                "Function not implemented for this kind of arguments: {}",
+                args
+            ),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct Delete;
+
+impl Op for Delete {
+    fn name(&self) -> &'static str {
+        "delete"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        match args.head() {
+            Some(Expression::Value(Value::Set(items))) => {
+                let mut items = items.clone();
+
+                for item in args.tail().iter() {
+                    items.remove(item);
+                }
+
+                Ok(Value::Set(items).into())
+            }
+            _ => bail!(
+                "Function not implemented for this kind of arguments: {}",
+                args
+            ),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct Union;
+
+impl Op for Union {
+    fn name(&self) -> &'static str {
+        "union"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        let union = sets(args)?
+            .into_iter()
+            .fold(HashSet::default(), |union, items| {
+                union.union(items.clone())
+            });
+
+        Ok(Value::Set(union).into())
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct Intersection;
+
+impl Op for Intersection {
+    fn name(&self) -> &'static str {
+        "intersection"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        let sets = sets(args)?;
+
+        let intersection = match sets.split_first() {
+            Some((first, rest)) => rest.iter().fold((*first).clone(), |intersection, items| {
+                intersection.intersection((*items).clone())
+            }),
+            None => bail!("Too few arguments given: {}", args),
+        };
+
+        Ok(Value::Set(intersection).into())
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct Difference;
+
+impl Op for Difference {
+    fn name(&self) -> &'static str {
+        "difference"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        let sets = sets(args)?;
+
+        let difference = match sets.split_first() {
+            Some((first, rest)) => rest.iter().fold((*first).clone(), |difference, items| {
+                difference.relative_complement((*items).clone())
+            }),
+            None => bail!("Too few arguments given: {}", args),
+        };
+
+        Ok(Value::Set(difference).into())
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct IsSubset;
+
+impl Op for IsSubset {
+    fn name(&self) -> &'static str {
+        "subset?"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        match sets(args)?.as_slice() {
+            [subset, superset] => Ok(Value::Bool(subset.is_subset(*superset)).into()),
+            _ => bail!("Expected exactly two sets, got: {}", args),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::list;
+
+    fn set(items: &[i64]) -> Expression {
+        Value::Set(
+            items
+                .iter()
+                .map(|i| Expression::from(Value::Int64(*i)))
+                .collect(),
+        )
+        .into()
+    }
+
+    #[test]
+    fn set_algebra() {
+        let env = Env::new();
+        let args = list![set(&[1, 2, 3]), set(&[2, 3, 4]), set(&[3, 5])];
+
+        assert_eq!(set(&[1, 2, 3, 4, 5]), Union.apply(&args, &env).unwrap());
+        assert_eq!(set(&[3]), Intersection.apply(&args, &env).unwrap());
+        assert_eq!(set(&[1]), Difference.apply(&args, &env).unwrap());
+        assert_eq!(set(&[]), Union.apply(&list![], &env).unwrap());
+        assert!(Intersection.apply(&list![], &env).is_err());
+    }
+
+    #[test]
+    fn add_does_not_change_original() {
+        let env = Env::new();
+        let original = set(&[1]);
+
+        assert_eq!(
+            set(&[1, 2]),
+            Add.apply(&list![original.clone(), Value::Int64(2).into()], &env)
+                .unwrap()
+        );
+        assert_eq!(set(&[1]), original);
+    }
+}
@@ -0,0 +1,308 @@
+use crate::data::{List, Vector};
+use crate::eval::env::Env;
+use crate::eval::native_calls::map::pair_to_entry;
+use crate::eval::native_calls::Op;
+use crate::eval::types::{Expression, Value};
+use anyhow::bail;
+
+fn to_index(index: &Expression, len: usize) -> anyhow::Result<usize> {
+    match index {
+        Expression::Value(Value::Int64(i)) if *i >= 0 && (*i as usize) <= len => Ok(*i as usize),
+        Expression::Value(Value::Int64(i)) => {
+            bail!("Index {} is out of bounds for length {}", i, len)
+        }
+        x => bail!("Index should be Int64, got: {}", x),
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct VectorConstructor;
+
+impl Op for VectorConstructor {
+    fn name(&self) -> &'static str {
+        "vector"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        Ok(Value::Vector(args.iter().cloned().collect()).into())
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct IntoVector;
+
+impl Op for IntoVector {
+    fn name(&self) -> &'static str {
+        "vec"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        match args.iter().collect::<Vec<_>>().as_slice() {
+            [Expression::List(list)] => Ok(Value::Vector(list.iter().cloned().collect()).into()),
+            [Expression::Value(Value::Vector(items))] => Ok(Value::Vector(items.clone()).into()),
+            [Expression::Value(Value::Set(items))] => {
+                Ok(Value::Vector(items.iter().cloned().collect()).into())
+            }
+            _ => bail!(
+                "Function not implemented for this kind of arguments: {}",
+                args
+            ),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct Nth;
+
+impl Op for Nth {
+    fn name(&self) -> &'static str {
+        "nth"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        let (index, len) = match args.iter().collect::<Vec<_>>().as_slice() {
+            [Expression::List(list), index] => {
+                let len = list.len() as usize;
+                let index = to_index(index, len)?;
+
+                if let Some(item) = list.iter().nth(index) {
+                    return Ok(item.clone());
+                }
+
+                (index, len)
+            }
+            [Expression::Value(Value::Vector(items)), index] => {
+                let index = to_index(index, items.len())?;
+
+                if let Some(item) = items.get(index) {
+                    return Ok(item.clone());
+                }
+
+                (index, items.len())
+            }
+            _ => bail!(
+                "Function not implemented for this kind of arguments: {}",
+                args
+            ),
+        };
+
+        bail!("Index {} is out of bounds for length {}", index, len)
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct Count;
+
+impl Op for Count {
+    fn name(&self) -> &'static str {
+        "count"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        let count = match args.iter().collect::<Vec<_>>().as_slice() {
+            [Expression::List(list)] => list.len(),
+            [Expression::Value(Value::Vector(items))] => items.len() as i64,
+            [Expression::Value(Value::Map(entries))] => entries.len() as i64,
+            [Expression::Value(Value::Set(items))] => items.len() as i64,
+            [Expression::Value(Value::String(string))] => string.chars().count() as i64,
+            [Expression::Value(Value::Nil)] => 0,
+            _ => bail!(
+                "Function not implemented for this kind of arguments: {}",
+                args
+            ),
+        };
+
+        Ok(Value::Int64(count).into())
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct Conj;
+
+impl Op for Conj {
+    fn name(&self) -> &'static str {
+        "conj"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        match args.head() {
+            Some(Expression::Value(Value::Vector(items))) => {
+                let mut items = items.clone();
+
+                for item in args.tail().iter() {
+                    items.push_back(item.clone());
+                }
+
+                Ok(Value::Vector(items).into())
+            }
+            Some(Expression::List(list)) => {
+                let mut list = List::clone(list);
+
+                for item in args.tail().iter() {
+                    list.push_top(item.clone());
+                }
+
+                Ok(list.into())
+            }
+            Some(Expression::Value(Value::Set(items))) => {
+                let mut items = items.clone();
+
+                for item in args.tail().iter() {
+                    items.insert(item.clone());
+                }
+
+                Ok(Value::Set(items).into())
+            }
+            Some(Expression::Value(Value::Map(entries))) => {
+                let mut entries = entries.clone();
+
+                for pair in args.tail().iter() {
+                    let (key, value) = pair_to_entry(pair)?;
+
+                    entries.insert(key, value);
+                }
+
+                Ok(Value::Map(entries).into())
+            }
+            _ => bail!(
+                "Function not implemented for this kind of arguments: {}",
+                args
+            ),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct Assoc;
+
+impl Op for Assoc {
+    fn name(&self) -> &'static str {
+        "assoc"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        let pairs = args.tail().iter().collect::<Vec<_>>();
+
+        if pairs.is_empty() || pairs.len() % 2 != 0 {
+            bail!("Expected collection and pairs of keys and values: {}", args);
+        }
+
+        match args.head() {
+            Some(Expression::Value(Value::Vector(items))) => {
+                let mut items = items.clone();
+
+                for pair in pairs.chunks(2) {
+                    let index = to_index(pair[0], items.len())?;
+
+                    if index == items.len() {
+                        items.push_back(pair[1].clone());
+                    } else {
+                        items.set(index, pair[1].clone());
+                    }
+                }
+
+                Ok(Value::Vector(items).into())
+            }
+            Some(Expression::Value(Value::Map(entries))) => {
+                let mut entries = entries.clone();
+
+                for pair in pairs.chunks(2) {
+                    entries.insert(pair[0].clone(), pair[1].clone());
+                }
+
+                Ok(Value::Map(entries).into())
+            }
+            _ => bail!(
+                "Function not implemented for this kind of arguments: {}",
+                args
+            ),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct Subvec;
+
+impl Op for Subvec {
+    fn name(&self) -> &'static str {
+        "subvec"
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        let (items, start, end) = match args.iter().collect::<Vec<_>>().as_slice() {
+            [Expression::Value(Value::Vector(items)), start] => {
+                (items, to_index(start, items.len())?, items.len())
+            }
+            [Expression::Value(Value::Vector(items)), start, end] => (
+                items,
+                to_index(start, items.len())?,
+                to_index(end, items.len())?,
+            ),
+            _ => bail!(
+                "Function not implemented for this kind of arguments: {}",
+                args
+            ),
+        };
+
+        if start > end {
+            bail!("Start index {} is greater than end index {}", start, end);
+        }
+
+        Ok(Value::Vector(Vector::clone(items).slice(start..end)).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::list;
+
+    fn vector(items: &[i64]) -> Expression {
+        Value::Vector(items.iter().map(|i| Value::Int64(*i).into()).collect()).into()
+    }
+
+    #[test]
+    fn assoc_does_not_change_original() {
+        let env = Env::new();
+        let original = vector(&[1, 2, 3]);
+
+        let updated = Assoc
+            .apply(
+                &list![
+                    original.clone(),
+                    Value::Int64(1).into(),
+                    Value::Int64(20).into()
+                ],
+                &env,
+            )
+            .unwrap();
+
+        assert_eq!(vector(&[1, 20, 3]), updated);
+        assert_eq!(vector(&[1, 2, 3]), original);
+    }
+
+    #[test]
+    fn subvec_bounds() {
+        let env = Env::new();
+        let v = vector(&[1, 2, 3, 4]);
+
+        assert_eq!(
+            vector(&[2, 3]),
+            Subvec
+                .apply(
+                    &list![v.clone(), Value::Int64(1).into(), Value::Int64(3).into()],
+                    &env
+                )
+                .unwrap()
+        );
+        assert!(Subvec
+            .apply(
+                &list![v.clone(), Value::Int64(3).into(), Value::Int64(1).into()],
+                &env
+            )
+            .is_err());
+        assert!(Subvec
+            .apply(&list![v, Value::Int64(5).into()], &env)
+            .is_err());
+    }
+}
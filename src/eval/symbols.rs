@@ -1,3 +1,28 @@
+use std::collections::HashSet;
+use std::sync::{Mutex, OnceLock};
+
+/// Names of symbols and bindings live as long as the program. Each name is
+/// leaked once and shared by all its uses, so reading and binding the same
+/// names again doesn't grow the memory.
+pub(crate) fn intern(name: &str) -> &'static str {
+    static NAMES: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+
+    let mut names = NAMES
+        .get_or_init(Mutex::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+
+    if let Some(interned) = names.get(name) {
+        return interned;
+    }
+
+    let interned: &'static str = Box::leak(name.to_string().into_boxed_str());
+
+    names.insert(interned);
+
+    interned
+}
+
 #[macro_export]
 macro_rules! symbol {
     ($name:literal) => {
@@ -39,3 +64,59 @@ macro_rules! loop_symbol {
         $crate::eval::types::Expression::Symbol("loop")
     };
 }
+
+#[macro_export]
+macro_rules! recur_symbol {
+    () => {
+        $crate::eval::types::Expression::Symbol("recur")
+    };
+}
+
+#[macro_export]
+macro_rules! eval_symbol {
+    () => {
+        $crate::eval::types::Expression::Symbol("eval")
+    };
+}
+
+#[macro_export]
+macro_rules! try_symbol {
+    () => {
+        $crate::eval::types::Expression::Symbol("try")
+    };
+}
+
+#[macro_export]
+macro_rules! quasiquote_symbol {
+    () => {
+        $crate::eval::types::Expression::Symbol("quasiquote")
+    };
+}
+
+#[macro_export]
+macro_rules! unquote_symbol {
+    () => {
+        $crate::eval::types::Expression::Symbol("unquote")
+    };
+}
+
+#[macro_export]
+macro_rules! unquote_splicing_symbol {
+    () => {
+        $crate::eval::types::Expression::Symbol("unquote-splicing")
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::intern;
+
+    #[test]
+    fn intern_names_once() {
+        let read = String::from("interned");
+        let name = intern(&read);
+
+        assert_eq!("interned", name);
+        assert!(std::ptr::eq(name, intern(&String::from("interned"))));
+    }
+}
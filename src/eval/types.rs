@@ -1,12 +1,19 @@
-use crate::data::List;
+use crate::data::{HashMap, HashSet, List, Vector};
 use crate::eval::env::Env;
 use crate::eval::native_calls::Op;
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use std::collections::hash_map::DefaultHasher;
 use std::fmt::{Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::mem::discriminant;
 use std::ops::Deref;
+use std::rc::Rc;
 use std::sync::Arc;
 
+/// Function implemented in Rust.
 #[derive(Clone)]
-pub(crate) struct NativeCall(pub(crate) Arc<Box<dyn Op>>);
+pub struct NativeCall(pub(crate) Arc<Box<dyn Op>>);
 
 impl Deref for NativeCall {
     type Target = Arc<Box<dyn Op>>;
@@ -20,15 +27,11 @@ impl PartialEq for NativeCall {
     fn eq(&self, other: &Self) -> bool {
         self.0.name() == other.0.name()
     }
-
-    fn ne(&self, other: &Self) -> bool {
-        self.0.name() != other.0.name()
-    }
 }
 
-impl Into<Expression> for NativeCall {
-    fn into(self) -> Expression {
-        Expression::Value(Value::NativeCall(self))
+impl From<NativeCall> for Expression {
+    fn from(call: NativeCall) -> Self {
+        Expression::Value(Value::NativeCall(call))
     }
 }
 
@@ -40,81 +43,277 @@ impl Debug for NativeCall {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
-pub(crate) enum Value {
+/// Evaluated value.
+#[derive(Debug, Clone)]
+pub enum Value {
     Int64(i64),
+    /// Integer out of the `Int64` range. Integers that fit are always `Int64`.
+    BigInt(BigInt),
+    /// Exact fraction in lowest terms. Ratios with denominator `1` are integers.
+    Ratio(BigRational),
     Float64(f64),
     String(String),
     Bool(bool),
     Nil,
+    Keyword(String),
+    Vector(Vector<Expression>),
+    Map(HashMap<Expression, Expression>),
+    Set(HashSet<Expression>),
     NativeCall(NativeCall),
     Lambda {
         env: Env,
-        args: Box<List<Expression>>,
-        body: Box<List<Expression>>,
+        args: Rc<List<Expression>>,
+        body: Rc<List<Expression>>,
+        doc: Option<String>,
     },
     Macro {
-        args: Box<List<Expression>>,
-        body: Box<List<Expression>>,
+        args: Rc<List<Expression>>,
+        body: Rc<List<Expression>>,
+        doc: Option<String>,
+    },
+    /// Error thrown by `throw` or raised by a native call, as caught by `try`.
+    Error {
+        kind: String,
+        message: String,
+        data: Box<Expression>,
     },
 }
 
+// Values are map keys and set items, so equality must be an equivalence: all
+// `NaN`s are equal to each other here, unlike in `=`, and both zeros are equal.
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Int64(a), Value::Int64(b)) => a == b,
+            (Value::BigInt(a), Value::BigInt(b)) => a == b,
+            (Value::Ratio(a), Value::Ratio(b)) => a == b,
+            (Value::Float64(a), Value::Float64(b)) => a == b || (a.is_nan() && b.is_nan()),
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Nil, Value::Nil) => true,
+            (Value::Keyword(a), Value::Keyword(b)) => a == b,
+            (Value::Vector(a), Value::Vector(b)) => a == b,
+            (Value::Map(a), Value::Map(b)) => a == b,
+            (Value::Set(a), Value::Set(b)) => a == b,
+            (Value::NativeCall(a), Value::NativeCall(b)) => a == b,
+            (
+                Value::Lambda {
+                    env,
+                    args,
+                    body,
+                    doc,
+                },
+                Value::Lambda {
+                    env: other_env,
+                    args: other_args,
+                    body: other_body,
+                    doc: other_doc,
+                },
+            ) => env == other_env && args == other_args && body == other_body && doc == other_doc,
+            (
+                Value::Macro { args, body, doc },
+                Value::Macro {
+                    args: other_args,
+                    body: other_body,
+                    doc: other_doc,
+                },
+            ) => args == other_args && body == other_body && doc == other_doc,
+            (
+                Value::Error {
+                    kind,
+                    message,
+                    data,
+                },
+                Value::Error {
+                    kind: other_kind,
+                    message: other_message,
+                    data: other_data,
+                },
+            ) => kind == other_kind && message == other_message && data == other_data,
+            _ => false,
+        }
+    }
+}
+
+/// Form of the program as read by the parser, or the result of its evaluation.
 #[derive(Debug, PartialEq, Clone)]
-pub(crate) enum Expression {
+pub enum Expression {
     Value(Value),
-    List(Box<List<Expression>>),
+    List(Rc<List<Expression>>),
     Symbol(&'static str),
     Dot,
 }
 
+// Expressions are used as map keys, so equal expressions must have equal hashes.
+// Both zeros hash the same, as do all `NaN`s, and lambdas are hashed without
+// their environment.
+impl Eq for Expression {}
+
+impl Hash for Expression {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        discriminant(self).hash(state);
+
+        match self {
+            Expression::Value(value) => value.hash(state),
+            Expression::List(list) => list.hash(state),
+            Expression::Symbol(name) => name.hash(state),
+            Expression::Dot => (),
+        }
+    }
+}
+
+// Iteration order of equal maps and sets is not guaranteed to be the same.
+fn hash_unordered<T: Hash, H: Hasher>(items: impl ExactSizeIterator<Item = T>, state: &mut H) {
+    items.len().hash(state);
+
+    items
+        .fold(0u64, |sum, item| {
+            let mut hasher = DefaultHasher::new();
+            item.hash(&mut hasher);
+            sum.wrapping_add(hasher.finish())
+        })
+        .hash(state);
+}
+
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        discriminant(self).hash(state);
+
+        match self {
+            Value::Int64(int) => int.hash(state),
+            Value::BigInt(big) => big.hash(state),
+            Value::Ratio(ratio) => ratio.hash(state),
+            Value::Float64(float) if *float == 0.0 => 0.0f64.to_bits().hash(state),
+            Value::Float64(float) if float.is_nan() => f64::NAN.to_bits().hash(state),
+            Value::Float64(float) => float.to_bits().hash(state),
+            Value::String(string) => string.hash(state),
+            Value::Bool(bool) => bool.hash(state),
+            Value::Nil => (),
+            Value::Keyword(name) => name.hash(state),
+            Value::Vector(items) => items.hash(state),
+            Value::Map(entries) => hash_unordered(entries.iter(), state),
+            Value::Set(items) => hash_unordered(items.iter(), state),
+            Value::NativeCall(call) => call.name().hash(state),
+            Value::Lambda { args, body, .. } | Value::Macro { args, body, .. } => {
+                args.hash(state);
+                body.hash(state);
+            }
+            Value::Error {
+                kind,
+                message,
+                data,
+            } => {
+                kind.hash(state);
+                message.hash(state);
+                data.hash(state);
+            }
+        }
+    }
+}
+
+impl Expression {
+    /// Name of the runtime variant of the expression.
+    pub(crate) fn type_name(&self) -> &'static str {
+        match self {
+            Expression::Value(Value::Int64(_)) => "Int64",
+            Expression::Value(Value::BigInt(_)) => "BigInt",
+            Expression::Value(Value::Ratio(_)) => "Ratio",
+            Expression::Value(Value::Float64(_)) => "Float64",
+            Expression::Value(Value::String(_)) => "String",
+            Expression::Value(Value::Bool(_)) => "Bool",
+            Expression::Value(Value::Nil) => "Nil",
+            Expression::Value(Value::Keyword(_)) => "Keyword",
+            Expression::Value(Value::Vector(_)) => "Vector",
+            Expression::Value(Value::Map(_)) => "Map",
+            Expression::Value(Value::Set(_)) => "Set",
+            Expression::Value(Value::NativeCall(_)) => "NativeCall",
+            Expression::Value(Value::Lambda { .. }) => "Lambda",
+            Expression::Value(Value::Macro { .. }) => "Macro",
+            Expression::Value(Value::Error { .. }) => "Error",
+            Expression::List(_) => "List",
+            Expression::Symbol(_) => "Symbol",
+            Expression::Dot => "Dot",
+        }
+    }
+}
+
 impl Default for Expression {
     fn default() -> Self {
         Expression::Value(Value::Nil)
     }
 }
 
+fn format_items<'a>(items: impl IntoIterator<Item = &'a Expression>) -> String {
+    items
+        .into_iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn format_body(doc: &Option<String>, body: &List<Expression>) -> String {
+    doc.iter()
+        .map(|doc| format!(r#""{}""#, doc))
+        .chain(body.iter().map(ToString::to_string))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 impl Display for Expression {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let str = match self {
             Expression::Dot => ".".to_string(),
             Expression::List(l) => format!("{}", l),
             Expression::Value(Value::Int64(int)) => format!("{}", int),
-            Expression::Value(Value::Float64(float)) => format!("{}", float),
+            Expression::Value(Value::BigInt(big)) => format!("{}", big),
+            Expression::Value(Value::Ratio(ratio)) => format!("{}", ratio),
+            Expression::Value(Value::Float64(float)) if float.is_nan() => "##NaN".to_string(),
+            Expression::Value(Value::Float64(float)) if float.is_infinite() => {
+                format!("##{}Inf", if *float < 0.0 { "-" } else { "" })
+            }
+            Expression::Value(Value::Float64(float)) => format!("{:?}", float),
             Expression::Value(Value::String(string)) => format!(r#""{}""#, string),
             Expression::Value(Value::Bool(bool)) => format!("{}", bool),
             Expression::Value(Value::Nil) => "Nil".to_string(),
+            Expression::Value(Value::Keyword(name)) => format!(":{}", name),
+            Expression::Value(Value::Vector(items)) => format!("[{}]", format_items(items)),
+            Expression::Value(Value::Map(entries)) => format!(
+                "{{{}}}",
+                format_items(entries.iter().flat_map(|(key, value)| [key, value]))
+            ),
+            Expression::Value(Value::Set(items)) => format!("#{{{}}}", format_items(items)),
             Expression::Value(Value::NativeCall(c)) => c.0.name().to_string(),
-            Expression::Symbol(symbol) => format!("{}", symbol),
-            Expression::Value(Value::Lambda { args, body, env: _ }) => {
-                let body_str = body
-                    .iter()
-                    .map(ToString::to_string)
-                    .collect::<Vec<_>>()
-                    .join(" ");
-                format!("(lambda {} {})", args, body_str)
+            Expression::Symbol(symbol) => symbol.to_string(),
+            Expression::Value(Value::Lambda {
+                args, body, doc, ..
+            }) => {
+                format!("(lambda {} {})", args, format_body(doc, body))
             }
-            Expression::Value(Value::Macro { args, body }) => {
-                let body_str = body
-                    .iter()
-                    .map(ToString::to_string)
-                    .collect::<Vec<_>>()
-                    .join(" ");
-                format!("(macro {} {})", args, body_str)
+            Expression::Value(Value::Macro { args, body, doc }) => {
+                format!("(macro {} {})", args, format_body(doc, body))
             }
+            Expression::Value(Value::Error {
+                kind,
+                message,
+                data,
+            }) => match data.as_ref() {
+                Expression::Value(Value::Nil) => format!(r#"#<error :{} "{}">"#, kind, message),
+                data => format!(r#"#<error :{} "{}" {}>"#, kind, message, data),
+            },
         };
 
         write!(f, "{}", str)
     }
 }
 
-impl Into<Expression> for Value {
-    fn into(self) -> Expression {
-        Expression::Value(self)
+impl From<Value> for Expression {
+    fn from(value: Value) -> Self {
+        Expression::Value(value)
     }
 }
 
-impl Into<Expression> for List<Expression> {
-    fn into(self) -> Expression {
-        Expression::List(Box::from(self))
+impl From<List<Expression>> for Expression {
+    fn from(list: List<Expression>) -> Self {
+        Expression::List(Rc::new(list))
     }
 }
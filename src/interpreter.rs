@@ -0,0 +1,540 @@
+use crate::data::List;
+use crate::eval;
+use crate::eval::convert::{TypedFn, TypedOp};
+use crate::eval::create_env;
+use crate::eval::env::Env;
+use crate::eval::error::EvalError;
+use crate::eval::eval::EvalStats;
+use crate::eval::native_calls::Op;
+use crate::eval::symbols::intern;
+use crate::eval::types::{Expression, NativeCall};
+use crate::read::{parse_with_source_map, SourceError, SourceMap};
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Place in the program an error comes from.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Location {
+    /// Name of the program, like the path of its file.
+    pub name: String,
+    /// Line and column (in chars) of the start, both starting from 1.
+    pub line: usize,
+    pub column: usize,
+    /// Byte offsets of the located form in the program.
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Error of the program, split by the stage it failed at. Parse and runtime
+/// errors have the location in the source they come from, when it is known.
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    Input {
+        message: String,
+    },
+    Parse {
+        message: String,
+        location: Option<Location>,
+    },
+    Runtime {
+        message: String,
+        location: Option<Location>,
+    },
+}
+
+impl Error {
+    pub fn message(&self) -> &str {
+        match self {
+            Error::Input { message }
+            | Error::Parse { message, .. }
+            | Error::Runtime { message, .. } => message,
+        }
+    }
+
+    pub fn location(&self) -> Option<&Location> {
+        match self {
+            Error::Input { .. } => None,
+            Error::Parse { location, .. } | Error::Runtime { location, .. } => location.as_ref(),
+        }
+    }
+
+    /*
+     Renders the error with the source line and a caret under the location:
+
+     error: Symbol foo is not defined
+      --> script.lisp:2:3
+       |
+     2 |   (+ 1 foo)
+       |   ^^^^^^^^^
+    */
+    /// Report of the error for a terminal. `program` is the source the error
+    /// comes from.
+    pub fn render(&self, program: &str) -> String {
+        let message = format!("error: {}", self.message());
+
+        let location = match self.location() {
+            Some(location) if location.end <= program.len() => location,
+            _ => return message,
+        };
+
+        let line_start = program[..location.start].rfind('\n').map_or(0, |i| i + 1);
+        let line = program[line_start..].lines().next().unwrap_or_default();
+        let underline_end = location
+            .end
+            .min(line_start + line.len())
+            .max(location.start);
+        let underline_len = program[location.start..underline_end]
+            .chars()
+            .count()
+            .max(1);
+
+        let line_number = location.line.to_string();
+        let gutter = " ".repeat(line_number.len());
+
+        format!(
+            "{}\n{}--> {}:{}:{}\n{} |\n{} | {}\n{} | {}{}",
+            message,
+            gutter,
+            location.name,
+            location.line,
+            location.column,
+            gutter,
+            line_number,
+            line,
+            gutter,
+            " ".repeat(location.column - 1),
+            "^".repeat(underline_len),
+        )
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for Error {}
+
+struct NativeFn<F> {
+    name: &'static str,
+    func: F,
+}
+
+impl<F> Op for NativeFn<F>
+where
+    F: Fn(&List<Expression>) -> anyhow::Result<Expression>,
+{
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn apply(&self, args: &List<Expression>, _env: &Env) -> anyhow::Result<Expression> {
+        (self.func)(args)
+    }
+}
+
+/// Evaluates the program in the environment. Errors point at the place in
+/// the source they come from.
+pub(crate) fn eval_source(name: &str, program: &str, env: &Env) -> Result<Expression, Error> {
+    eval_source_with_stats(name, program, env).map(|(result, _)| result)
+}
+
+pub(crate) fn eval_source_with_stats(
+    name: &str,
+    program: &str,
+    env: &Env,
+) -> Result<(Expression, EvalStats), Error> {
+    let (expressions, source_map) = parse_with_source_map(program).map_err(|e| Error::Parse {
+        message: e.to_string(),
+        location: error_location(name, &SourceMap::default(), &e),
+    })?;
+
+    eval::eval_with_stats(&expressions, env).map_err(|e| Error::Runtime {
+        message: e.to_string(),
+        location: error_location(name, &source_map, &e),
+    })
+}
+
+fn error_location(name: &str, source_map: &SourceMap, error: &anyhow::Error) -> Option<Location> {
+    let span = match error.downcast_ref::<SourceError>() {
+        Some(error) => error.span,
+        None => {
+            let error = error.downcast_ref::<EvalError>()?;
+
+            error.forms.iter().find_map(|form| source_map.get(*form))?
+        }
+    };
+
+    Some(Location {
+        name: name.to_string(),
+        line: span.line,
+        column: span.column,
+        start: span.start,
+        end: span.end,
+    })
+}
+
+/// Interpreter with its own global environment, for embedding iamlisp into
+/// Rust programs.
+///
+/// ```
+/// use iamlisp::{Expression, Interpreter, Value};
+///
+/// let mut interpreter = Interpreter::new();
+///
+/// interpreter.set_global("x", Value::Int64(2));
+/// interpreter.register_fn("twice", |args| match args.head() {
+///     Some(Expression::Value(Value::Int64(x))) => Ok(Value::Int64(x * 2).into()),
+///     _ => anyhow::bail!("Expected integer: {}", args),
+/// });
+///
+/// assert_eq!(
+///     Ok(Value::Int64(4).into()),
+///     interpreter.eval_str("(twice x)")
+/// );
+/// ```
+pub struct Interpreter {
+    env: Env,
+}
+
+impl Interpreter {
+    /// Interpreter with all native calls loaded.
+    pub fn new() -> Self {
+        Self { env: create_env() }
+    }
+
+    /// Evaluates the program and returns the result of its last expression.
+    pub fn eval_str(&self, program: &str) -> Result<Expression, Error> {
+        self.eval_source("<string>", program)
+    }
+
+    /// Evaluates the program from the file. `#!` line at its start is a comment.
+    pub fn eval_file(&self, path: impl AsRef<Path>) -> Result<Expression, Error> {
+        let path = path.as_ref();
+        let program = std::fs::read_to_string(path).map_err(|e| Error::Input {
+            message: format!("Cannot read {}: {}", path.display(), e),
+        })?;
+
+        self.eval_source(&path.display().to_string(), &program)
+    }
+
+    /// Evaluates the program, with `name` used in error locations.
+    pub fn eval_source(&self, name: &str, program: &str) -> Result<Expression, Error> {
+        eval_source(name, program, &self.env)
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<Expression> {
+        self.env.get(name)
+    }
+
+    pub fn set_global(&mut self, name: &str, value: impl Into<Expression>) {
+        self.env.set(intern(name), value.into());
+    }
+
+    /// Binds a native call, which gets already evaluated arguments.
+    pub fn register_fn<F>(&mut self, name: &str, func: F)
+    where
+        F: Fn(&List<Expression>) -> anyhow::Result<Expression> + 'static,
+    {
+        let name = intern(name);
+
+        self.set_global(
+            name,
+            NativeCall(Arc::new(Box::new(NativeFn { name, func }))),
+        );
+    }
+
+    /// Binds a Rust function with arguments converted by `FromValue` and
+    /// result converted by `IntoValue`. The number and types of arguments are
+    /// checked before the call.
+    ///
+    /// ```
+    /// use iamlisp::{Interpreter, Value};
+    ///
+    /// let mut interpreter = Interpreter::new();
+    ///
+    /// interpreter.register_typed_fn("repeat", |count: i64, text: String| {
+    ///     Ok(text.repeat(count as usize))
+    /// });
+    ///
+    /// assert_eq!(
+    ///     Ok(Value::String("abab".to_string()).into()),
+    ///     interpreter.eval_str(r#"(repeat 2 "ab")"#)
+    /// );
+    /// ```
+    pub fn register_typed_fn<F, Args>(&mut self, name: &str, func: F)
+    where
+        F: TypedFn<Args> + 'static,
+        Args: 'static,
+    {
+        let name = intern(name);
+
+        self.set_global(
+            name,
+            NativeCall(Arc::new(Box::new(TypedOp::new(name, func)))),
+        );
+    }
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::types::Value;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn globals() {
+        let mut interpreter = Interpreter::new();
+
+        interpreter.set_global("x", Value::Int64(1));
+        interpreter.eval_str("(def y (+ x 1))").unwrap();
+
+        assert_eq!(Some(Value::Int64(2).into()), interpreter.get_global("y"));
+        assert_eq!(None, interpreter.get_global("z"));
+    }
+
+    #[test]
+    fn registered_fn() {
+        let mut interpreter = Interpreter::new();
+
+        interpreter.register_fn("count-args", |args| Ok(Value::Int64(args.len()).into()));
+
+        assert_eq!(
+            Ok(Value::Int64(3).into()),
+            interpreter.eval_str("(count-args 1 (+ 1 1) 3)")
+        );
+        assert_eq!(
+            Ok(Value::Int64(0).into()),
+            interpreter.eval_str("(begin (def f count-args) (f))")
+        );
+    }
+
+    #[test]
+    fn registered_typed_fn() {
+        let mut interpreter = Interpreter::new();
+
+        interpreter.register_typed_fn("starts-with", |text: String, prefix: String| {
+            Ok(text.starts_with(&prefix))
+        });
+        interpreter.register_typed_fn("sum", |items: Vec<f64>, init: Option<f64>| {
+            Ok(items.into_iter().sum::<f64>() + init.unwrap_or_default())
+        });
+
+        assert_eq!(
+            Ok(Value::Bool(true).into()),
+            interpreter.eval_str(r#"(starts-with "iamlisp" "iam")"#)
+        );
+        assert_eq!(
+            Ok(Value::Float64(3.5).into()),
+            interpreter.eval_str("(sum [1 2.5] Nil)")
+        );
+        assert_eq!(
+            Ok(Value::Float64(4.5).into()),
+            interpreter.eval_str("(sum '(1 2.5) 1)")
+        );
+        assert!(interpreter
+            .eval_str(r#"(starts-with "iamlisp" 1)"#)
+            .unwrap_err()
+            .to_string()
+            .starts_with("expected String at argument 2, got Int64"));
+    }
+
+    #[test]
+    fn try_unwinds_across_lambdas() {
+        let mut interpreter = Interpreter::new();
+        let log = Rc::new(RefCell::new(vec![]));
+        let notes = log.clone();
+
+        interpreter.register_fn("note", move |args| {
+            notes.borrow_mut().push(args.to_string());
+            Ok(Expression::default())
+        });
+        interpreter
+            .eval_str(
+                "(defun risky (n)
+                   (try (cond (> n 0) (risky (- n 1)) (car 1))
+                        (finally (note n))))",
+            )
+            .unwrap();
+
+        assert_eq!(
+            Ok(Value::Keyword("caught".to_string()).into()),
+            interpreter.eval_str("(try (risky 2) (catch e :caught))")
+        );
+        assert_eq!(vec!["(0)", "(1)", "(2)"], log.take());
+
+        assert!(interpreter
+            .eval_str("(risky 1)")
+            .unwrap_err()
+            .to_string()
+            .starts_with("Function not implemented for this kind of arguments: (1)"));
+        assert_eq!(vec!["(0)", "(1)"], log.take());
+
+        assert_eq!(
+            Ok(Value::Int64(1).into()),
+            interpreter.eval_str("(try 1 (catch e 2) (finally (note :done)))")
+        );
+        assert_eq!(
+            Ok(Value::Int64(2).into()),
+            interpreter.eval_str("(try (throw 1) (catch e 2) (finally (note :caught)))")
+        );
+        assert!(interpreter
+            .eval_str("(try (throw 1) (catch e (throw 3)) (finally (note :rethrown)))")
+            .is_err());
+        assert_eq!(vec!["(:done)", "(:caught)", "(:rethrown)"], log.take());
+
+        assert_eq!(
+            Ok(Value::String("finally".to_string()).into()),
+            interpreter.eval_str(
+                r#"(try (try (throw 1) (finally (throw "finally")))
+                        (catch e (error-message e)))"#
+            )
+        );
+        assert_eq!(
+            Ok(Value::Int64(42).into()),
+            interpreter.eval_str(
+                "(try (try (throw 42) (finally (note :unwound)))
+                      (catch e (error-data e)))"
+            )
+        );
+        assert_eq!(vec!["(:unwound)"], log.take());
+
+        // Natives named `try` are ordinary calls.
+        interpreter.register_fn("try", |_| Ok(Value::Int64(7).into()));
+
+        assert_eq!(
+            Ok(Value::Int64(7).into()),
+            interpreter.eval_str("(def native-try try) (native-try)")
+        );
+        assert_eq!(
+            Ok(Value::Int64(7).into()),
+            interpreter.eval_str("(try (native-try) (catch e 0))")
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let interpreter = Interpreter::new();
+
+        assert_eq!(
+            Err(Error::Runtime {
+                message: "Symbol foo is not defined".to_string(),
+                location: Some(Location {
+                    name: "<string>".to_string(),
+                    line: 2,
+                    column: 3,
+                    start: 10,
+                    end: 15,
+                }),
+            }),
+            interpreter.eval_str("(+ 1 2)\n  (foo)")
+        );
+        assert!(matches!(
+            interpreter.eval_str("(+ 1"),
+            Err(Error::Parse {
+                location: Some(Location {
+                    line: 1,
+                    column: 1,
+                    ..
+                }),
+                ..
+            })
+        ));
+        assert!(matches!(
+            interpreter.eval_file("missing.lisp"),
+            Err(Error::Input { .. })
+        ));
+    }
+
+    #[test]
+    fn shebang_line() {
+        let interpreter = Interpreter::new();
+
+        assert_eq!(
+            Ok(Value::Int64(3).into()),
+            interpreter.eval_str("#!/usr/bin/env iamlisp\n(+ 1 2)")
+        );
+        assert_eq!(
+            Some(2),
+            interpreter
+                .eval_str("#!/usr/bin/env iamlisp\n(foo)")
+                .unwrap_err()
+                .location()
+                .map(|location| location.line)
+        );
+        assert!(interpreter.eval_str("(+ 1 2) #!").is_err());
+    }
+
+    #[test]
+    fn error_locations() {
+        let env = create_env();
+
+        let table = vec![
+            (
+                "(+ 1 2)\n  (+ 1 foo)",
+                "error: Symbol foo is not defined\n --> test.lisp:2:3\n  |\n2 |   (+ 1 foo)\n  |   ^^^^^^^^^",
+            ),
+            (
+                "(defun f (x)\n  (+ x (g x)))\n(f 1)",
+                "error: Symbol g is not defined\n --> test.lisp:2:8\n  |\n2 |   (+ x (g x)))\n  |        ^^^^^",
+            ),
+            (
+                "(list 1\n  (nth [1] 5))",
+                "error: Index 5 is out of bounds for length 1\n --> test.lisp:2:3\n  |\n2 |   (nth [1] 5))\n  |   ^^^^^^^^^^^",
+            ),
+            (
+                "(list 1\n  2",
+                "error: Compile error: unexpected end of program while reading list\n --> test.lisp:1:1\n  |\n1 | (list 1\n  | ^",
+            ),
+            (
+                "(print \"a)",
+                "error: Unexpected end of input on reading string\n --> test.lisp:1:8\n  |\n1 | (print \"a)\n  |        ^^^",
+            ),
+            ("foo", "error: Symbol foo is not defined"),
+        ];
+
+        for (program, expected_error) in table {
+            assert_eq!(
+                eval_source("test.lisp", program, &env)
+                    .err()
+                    .map(|e| e.render(program)),
+                Some(expected_error.to_string()),
+                "{}",
+                program
+            );
+        }
+
+        assert!(matches!(
+            eval_source("test.lisp", "(", &env),
+            Err(Error::Parse { .. })
+        ));
+        assert!(matches!(
+            eval_source("test.lisp", "(foo)", &env),
+            Err(Error::Runtime { .. })
+        ));
+    }
+
+    #[test]
+    fn render_errors() {
+        let interpreter = Interpreter::new();
+        let program = "(+ 1 2)\n  (+ 1 foo)";
+
+        assert_eq!(
+            "error: Symbol foo is not defined\n --> <string>:2:3\n  |\n2 |   (+ 1 foo)\n  |   ^^^^^^^^^",
+            interpreter.eval_str(program).unwrap_err().render(program)
+        );
+        assert_eq!(
+            "error: Symbol foo is not defined",
+            interpreter.eval_str("foo").unwrap_err().render("foo")
+        );
+    }
+}
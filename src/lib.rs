@@ -0,0 +1,20 @@
+//! iamlisp interpreter with iterative evaluation of programs.
+//!
+//! [`Interpreter`] evaluates programs in its own global environment, and Rust
+//! functions can be registered in it as native calls.
+
+mod data;
+mod eval;
+mod interpreter;
+mod read;
+#[cfg(feature = "cli")]
+pub mod repl;
+
+pub use data::{HashMap, HashSet, List, Vector};
+pub use eval::convert::{FromValue, IntoValue, TypedFn};
+pub use eval::env::Env;
+pub use eval::native_calls::{Continuation, NativeResult, Op};
+pub use eval::types::{Expression, NativeCall, Value};
+pub use interpreter::{Error, Interpreter, Location};
+pub use num_bigint::BigInt;
+pub use num_rational::BigRational;
@@ -1,8 +1,7 @@
-mod data;
-mod eval;
-mod read;
-mod repl;
+mod cli;
 
 fn main() {
-    println!("Hello, world!");
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    std::process::exit(cli::run(&args));
 }
@@ -1,13 +1,49 @@
 use crate::data::List;
 use crate::eval::types::Expression;
 use crate::read::parser::Parser;
+pub(crate) use crate::read::source::{FormId, SourceError, SourceMap};
 use crate::read::tokenize::tokenize;
+#[cfg(feature = "cli")]
+use crate::read::tokenize::Token;
 
 mod parser;
+mod source;
 mod tokenize;
 
+#[cfg(test)]
 pub(crate) fn parse(program: &str) -> anyhow::Result<List<Expression>> {
-    let tokens = tokenize(program.chars()).unwrap();
+    parse_with_source_map(program).map(|(expressions, _)| expressions)
+}
+
+/// Whether the program ends inside of a string or a list, vector, map or set,
+/// or right after a quote prefix, so more input is expected.
+#[cfg(feature = "cli")]
+pub(crate) fn is_incomplete(program: &str) -> bool {
+    // Other tokenizer errors, like invalid numbers, are not fixed by more input.
+    let tokens = match tokenize(program) {
+        Ok(tokens) => tokens,
+        Err(error) => return program[error.span.start..].starts_with('"'),
+    };
+
+    let depth = tokens.iter().fold(0, |depth, token| match token.token {
+        Token::LeftParen | Token::LeftSquareBracket | Token::LeftBracket => depth + 1,
+        Token::RightParen | Token::RightSquareBracket | Token::RightBracket => depth - 1,
+        _ => depth,
+    });
+
+    let ends_with_prefix = matches!(
+        tokens.last().map(|token| &token.token),
+        Some(Token::SingleQuote | Token::Backquote | Token::Comma | Token::CommaAt | Token::Sharp)
+    );
+
+    depth > 0 || ends_with_prefix
+}
+
+/// Parses the program keeping source locations of the parsed lists.
+pub(crate) fn parse_with_source_map(
+    program: &str,
+) -> anyhow::Result<(List<Expression>, SourceMap)> {
+    let tokens = tokenize(program)?;
 
     Parser::new(tokens).parse()
 }
@@ -15,8 +51,14 @@ pub(crate) fn parse(program: &str) -> anyhow::Result<List<Expression>> {
 #[cfg(test)]
 mod tests {
     use super::parse;
+    use crate::data::{HashMap, Vector};
+    use crate::eval::types::Expression;
     use crate::eval::types::Value;
-    use crate::{list, symbol};
+    use crate::{
+        list, quasiquote_symbol, quote_symbol, symbol, unquote_splicing_symbol, unquote_symbol,
+    };
+    use num_bigint::BigInt;
+    use num_rational::BigRational;
 
     #[test]
     fn read_nested_lists() {
@@ -39,6 +81,29 @@ mod tests {
         assert_eq!(list![Value::Int64(0).into()], parse("0").unwrap());
         assert_eq!(list![Value::Int64(10).into()], parse("10").unwrap());
         assert_eq!(list![Value::Int64(-10).into()], parse("-10").unwrap());
+        assert_eq!(
+            list![Value::BigInt("-9223372036854775809".parse().unwrap()).into()],
+            parse("-9223372036854775809").unwrap()
+        );
+        assert_eq!(
+            list![Value::BigInt(BigInt::from(u64::MAX)).into()],
+            parse("18446744073709551615").unwrap()
+        );
+    }
+
+    #[test]
+    fn read_ratio() {
+        assert_eq!(
+            list![Value::Ratio(BigRational::new(1.into(), 3.into())).into()],
+            parse("1/3").unwrap()
+        );
+        assert_eq!(
+            list![Value::Ratio(BigRational::new((-1).into(), 2.into())).into()],
+            parse("-2/4").unwrap()
+        );
+        assert_eq!(list![Value::Int64(2).into()], parse("4/2").unwrap());
+        assert!(parse("1/0").is_err());
+        assert!(parse("1/-2").is_err());
     }
 
     #[test]
@@ -48,6 +113,68 @@ mod tests {
         assert_eq!(list![Value::Float64(-10.0).into()], parse("-10.0").unwrap());
     }
 
+    #[test]
+    fn read_number_literals() {
+        let table = vec![
+            ("0xff", Value::Int64(255)),
+            ("-0x10", Value::Int64(-16)),
+            ("0o17", Value::Int64(15)),
+            ("0b1010", Value::Int64(10)),
+            ("1_000_000", Value::Int64(1_000_000)),
+            (
+                "0xffff_ffff_ffff_ffff",
+                Value::BigInt(BigInt::from(u64::MAX)),
+            ),
+            ("+5", Value::Int64(5)),
+            ("1e3", Value::Float64(1000.0)),
+            ("1.5E-3", Value::Float64(0.0015)),
+            ("-2.5e+2", Value::Float64(-250.0)),
+            (".5", Value::Float64(0.5)),
+            ("1.", Value::Float64(1.0)),
+            ("1_000.000_1", Value::Float64(1000.0001)),
+            ("##Inf", Value::Float64(f64::INFINITY)),
+            ("##-Inf", Value::Float64(f64::NEG_INFINITY)),
+        ];
+
+        for (program, expected) in table {
+            assert_eq!(
+                list![expected.into()],
+                parse(program).unwrap(),
+                "{}",
+                program
+            );
+        }
+
+        assert!(matches!(
+            parse("##NaN").unwrap().head(),
+            Some(Expression::Value(Value::Float64(float))) if float.is_nan()
+        ));
+
+        for program in ["inf", "NaN", "-inf", "infinity"] {
+            assert_eq!(list![Expression::Symbol(program)], parse(program).unwrap());
+        }
+
+        for program in [
+            "12abc", "0x", "0xfg", "1__0", "1_", "1e", "1.5.2", "0b102", "1-2", "##Foo",
+        ] {
+            assert!(parse(program).is_err(), "{}", program);
+        }
+
+        assert_eq!(
+            "Invalid number: 12abc",
+            parse("(+ 12abc 1)").unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Number is out of range: 1e400",
+            parse("1e400").unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Number is out of range: -1e400",
+            parse("-1e400").unwrap_err().to_string()
+        );
+        assert_eq!(list![Value::Float64(0.0).into()], parse("1e-400").unwrap());
+    }
+
     #[test]
     fn read_string() {
         assert_eq!(
@@ -69,6 +196,115 @@ mod tests {
         );
     }
 
+    #[test]
+    fn read_quote() {
+        assert_eq!(
+            list![list![quote_symbol!(), symbol!("a")].into()],
+            parse("'a").unwrap()
+        );
+        assert_eq!(
+            list![list![
+                quote_symbol!(),
+                list![Value::Int64(1).into(), Value::Int64(2).into()].into()
+            ]
+            .into()],
+            parse("'(1 2)").unwrap()
+        );
+        assert!(parse("(a ')").is_err());
+    }
+
+    #[test]
+    fn read_quasiquote() {
+        assert_eq!(
+            list![list![
+                quasiquote_symbol!(),
+                list![
+                    symbol!("a"),
+                    list![unquote_symbol!(), symbol!("b")].into(),
+                    list![unquote_splicing_symbol!(), symbol!("c")].into()
+                ]
+                .into()
+            ]
+            .into()],
+            parse("`(a ,b ,@c)").unwrap()
+        );
+    }
+
+    #[test]
+    fn read_vector() {
+        assert_eq!(
+            list![Value::Vector(
+                vec![
+                    Value::Int64(1).into(),
+                    symbol!("a"),
+                    Value::Vector(Vector::new()).into()
+                ]
+                .into()
+            )
+            .into()],
+            parse("[1 a []]").unwrap()
+        );
+        assert!(parse("[1 2").is_err());
+        assert!(parse("[1 2)").is_err());
+    }
+
+    #[test]
+    fn read_map() {
+        let mut entries = HashMap::default();
+
+        entries.insert(
+            Value::Keyword("a".to_string()).into(),
+            Value::Int64(1).into(),
+        );
+        entries.insert(Value::String("b".to_string()).into(), symbol!("c"));
+
+        assert_eq!(
+            list![Value::Map(entries).into()],
+            parse(r#"{:a 1 "b" c}"#).unwrap()
+        );
+        assert!(parse("{:a 1 :b}").is_err());
+        assert!(parse("{:a 1").is_err());
+    }
+
+    #[test]
+    fn read_set() {
+        assert_eq!(
+            list![Value::Set(
+                vec![Value::Int64(1).into(), symbol!("a")]
+                    .into_iter()
+                    .collect()
+            )
+            .into()],
+            parse("#{1 a 1}").unwrap()
+        );
+        assert!(parse("#(1)").is_err());
+        assert!(parse("#{1").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn read_incomplete_program() {
+        use super::is_incomplete;
+
+        let table = vec![
+            ("(+ 1 2)", false),
+            ("(+ 1 2))", false),
+            ("", false),
+            ("(+ 1\n  (- 2", true),
+            ("[1 {:a", true),
+            ("#{1", true),
+            (r#"(print "a)"#, true),
+            ("(print \")\")", false),
+            ("; (", false),
+            ("'", true),
+            ("(+ 12abc", false),
+        ];
+
+        for (program, expected) in table {
+            assert_eq!(expected, is_incomplete(program), "{}", program);
+        }
+    }
+
     #[test]
     fn read_whole_program() {
         let program = r#"(+ (foo 1 "hello") 12.5)"#;
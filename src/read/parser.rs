@@ -1,60 +1,178 @@
-use crate::data::List;
+use crate::data::{HashMap, HashSet, List};
+use crate::eval::symbols::intern;
 use crate::eval::types::{Expression, Value};
-use crate::list;
-use crate::read::tokenize::Token;
-use anyhow::bail;
+use crate::read::source::{SourceError, SourceMap, Span};
+use crate::read::tokenize::{SpannedToken, Token};
+use crate::{list, quasiquote_symbol, quote_symbol, unquote_splicing_symbol, unquote_symbol};
+use std::rc::Rc;
+
+fn error(span: Span, message: String) -> anyhow::Error {
+    SourceError { span, message }.into()
+}
 
 pub(crate) struct Parser {
-    program_iter: Box<dyn Iterator<Item = Token>>,
+    program_iter: Box<dyn Iterator<Item = SpannedToken>>,
+    source_map: SourceMap,
+    last_span: Option<Span>,
 }
 
 impl Parser {
-    pub(crate) fn new(program_tokens: Vec<Token>) -> Self {
+    pub(crate) fn new(program_tokens: Vec<SpannedToken>) -> Self {
         Self {
             program_iter: Box::new(program_tokens.into_iter()),
+            source_map: SourceMap::default(),
+            last_span: None,
         }
     }
 
-    pub(crate) fn parse(mut self) -> anyhow::Result<List<Expression>> {
+    pub(crate) fn parse(mut self) -> anyhow::Result<(List<Expression>, SourceMap)> {
         let mut expressions = list![];
 
-        while let Some(token) = self.program_iter.next() {
-            expressions.push(match token {
-                Token::Symbol(name) => Expression::Symbol(Box::leak(name.into_boxed_str())),
-                Token::String(text) => Value::String(text).into(),
-                Token::Int64(int) => Value::Int64(int).into(),
-                Token::Float64(float) => Value::Float64(float).into(),
-                Token::Boolean(bool) => Value::Bool(bool).into(),
-                Token::Nil => Value::Nil.into(),
-                Token::Dot => Expression::Dot,
-                Token::LeftParen => self.parse_list()?.into(),
-                t => bail!("Compile error: unexpected token: {:?}", t),
-            });
+        while let Some(token) = self.next_token() {
+            expressions.push(self.parse_expression(token)?);
         }
 
-        Ok(expressions)
+        Ok((expressions, self.source_map))
     }
 
-    fn parse_list(&mut self) -> anyhow::Result<List<Expression>> {
-        let mut expressions = list![];
+    fn next_token(&mut self) -> Option<SpannedToken> {
+        let token = self.program_iter.next();
+
+        if let Some(SpannedToken { span, .. }) = &token {
+            self.last_span = Some(*span);
+        }
+
+        token
+    }
+
+    /// Span from the given one to the end of the last read token.
+    fn span_from(&self, span: Span) -> Span {
+        self.last_span.map_or(span, |last_span| span.to(last_span))
+    }
+
+    /// Lists get their location recorded in the source map.
+    fn located_list(&mut self, list: List<Expression>, span: Span) -> Expression {
+        let list = Rc::new(list);
+
+        self.source_map.insert(&list, self.span_from(span));
+
+        Expression::List(list)
+    }
+
+    fn parse_expression(&mut self, token: SpannedToken) -> anyhow::Result<Expression> {
+        let SpannedToken { token, span } = token;
+
+        Ok(match token {
+            Token::Symbol(name) => Expression::Symbol(intern(&name)),
+            Token::Keyword(name) => Value::Keyword(name).into(),
+            Token::String(text) => Value::String(text).into(),
+            Token::Int64(int) => Value::Int64(int).into(),
+            Token::BigInt(big) => Value::BigInt(big).into(),
+            Token::Ratio(ratio) => Value::Ratio(ratio).into(),
+            Token::Float64(float) => Value::Float64(float).into(),
+            Token::Boolean(bool) => Value::Bool(bool).into(),
+            Token::Nil => Value::Nil.into(),
+            Token::Dot => Expression::Dot,
+            Token::LeftParen => {
+                let items = self.parse_sequence(Token::RightParen, "list", span)?;
+
+                self.located_list(items, span)
+            }
+            Token::LeftSquareBracket => {
+                let items = self.parse_sequence(Token::RightSquareBracket, "vector", span)?;
+
+                Value::Vector(items.into_iter().collect()).into()
+            }
+            Token::LeftBracket => {
+                let items = self.parse_sequence(Token::RightBracket, "map", span)?;
 
-        while let Some(token) = self.program_iter.next() {
-            expressions.push(match token {
-                Token::Symbol(name) => Expression::Symbol(Box::leak(name.into_boxed_str())),
-                Token::String(text) => Value::String(text).into(),
-                Token::Int64(int) => Value::Int64(int).into(),
-                Token::Float64(float) => Value::Float64(float).into(),
-                Token::Boolean(bool) => Value::Bool(bool).into(),
-                Token::Nil => Value::Nil.into(),
-                Token::Dot => Expression::Dot,
-                Token::LeftParen => self.parse_list()?.into(),
-                Token::RightParen => {
-                    return Ok(expressions);
+                if items.len() % 2 != 0 {
+                    return Err(error(
+                        self.span_from(span),
+                        "Compile error: map literal should contain pairs of keys and values"
+                            .to_string(),
+                    ));
                 }
-                t => bail!("Compile error: unexpected token: {:?}", t),
-            });
+
+                let mut items = items.into_iter();
+                let mut entries = HashMap::default();
+
+                while let (Some(key), Some(value)) = (items.next(), items.next()) {
+                    entries.insert(key, value);
+                }
+
+                Value::Map(entries).into()
+            }
+            Token::Sharp => match self.next_token() {
+                Some(SpannedToken {
+                    token: Token::LeftBracket,
+                    ..
+                }) => {
+                    let items = self.parse_sequence(Token::RightBracket, "set", span)?;
+
+                    Value::Set(items.into_iter().collect::<HashSet<_>>()).into()
+                }
+                _ => {
+                    return Err(error(
+                        span,
+                        "Compile error: set literal is expected after #".to_string(),
+                    ))
+                }
+            },
+            Token::SingleQuote => self.parse_prefixed(quote_symbol!(), span)?,
+            Token::Backquote => self.parse_prefixed(quasiquote_symbol!(), span)?,
+            Token::Comma => self.parse_prefixed(unquote_symbol!(), span)?,
+            Token::CommaAt => self.parse_prefixed(unquote_splicing_symbol!(), span)?,
+            t => {
+                return Err(error(
+                    span,
+                    format!("Compile error: unexpected token: {:?}", t),
+                ))
+            }
+        })
+    }
+
+    /// Reads `'x` as `(quote x)`, `` `x `` as `(quasiquote x)` and so on.
+    fn parse_prefixed(&mut self, symbol: Expression, span: Span) -> anyhow::Result<Expression> {
+        match self.next_token() {
+            Some(SpannedToken {
+                token: Token::RightParen | Token::RightSquareBracket,
+                ..
+            })
+            | None => Err(error(
+                span,
+                format!("Compile error: expression is expected after {}", symbol),
+            )),
+            Some(token) => {
+                let expression = self.parse_expression(token)?;
+
+                Ok(self.located_list(list![symbol, expression], span))
+            }
+        }
+    }
+
+    fn parse_sequence(
+        &mut self,
+        closing: Token,
+        kind: &str,
+        span: Span,
+    ) -> anyhow::Result<List<Expression>> {
+        let mut expressions = list![];
+
+        while let Some(token) = self.next_token() {
+            if token.token == closing {
+                return Ok(expressions.reverse());
+            }
+
+            expressions.push_top(self.parse_expression(token)?);
         }
 
-        bail!("Compile error: unexpected end of program while reading list")
+        Err(error(
+            span,
+            format!(
+                "Compile error: unexpected end of program while reading {}",
+                kind
+            ),
+        ))
     }
 }
@@ -0,0 +1,155 @@
+use crate::data::List;
+use crate::eval::types::Expression;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::rc::{Rc, Weak};
+
+/// Location of a piece of source: byte offsets `start..end` and 1-based line
+/// and column (in chars) of the start.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(crate) struct Span {
+    pub(crate) start: usize,
+    pub(crate) end: usize,
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+}
+
+impl Span {
+    /// Span from the start of this span to the end of the other one.
+    pub(crate) fn to(self, other: Span) -> Span {
+        Span {
+            end: other.end,
+            ..self
+        }
+    }
+}
+
+/// Converts byte offsets of the source to line and column.
+pub(crate) struct LineIndex<'a> {
+    source: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub(crate) fn new(source: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+
+        Self {
+            source,
+            line_starts,
+        }
+    }
+
+    pub(crate) fn span(&self, start: usize, end: usize) -> Span {
+        let line = self
+            .line_starts
+            .partition_point(|line_start| *line_start <= start);
+        let column = self.source[self.line_starts[line - 1]..start]
+            .chars()
+            .count()
+            + 1;
+
+        Span {
+            start,
+            end,
+            line,
+            column,
+        }
+    }
+}
+
+/// Error found while reading the source, like an unclosed list.
+#[derive(Debug)]
+pub(crate) struct SourceError {
+    pub(crate) span: Span,
+    pub(crate) message: String,
+}
+
+impl Display for SourceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for SourceError {}
+
+/// Identity of a parsed list, which survives cloning of `Expression::List`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub(crate) struct FormId(usize);
+
+impl FormId {
+    pub(crate) fn of(list: &Rc<List<Expression>>) -> Self {
+        FormId(Rc::as_ptr(list) as usize)
+    }
+}
+
+/// Side table of source locations of the parsed lists. Lists are referenced
+/// weakly, so a location is never reported for another list reusing the
+/// address of a dropped one.
+#[derive(Default)]
+pub(crate) struct SourceMap {
+    spans: HashMap<FormId, (Weak<List<Expression>>, Span)>,
+}
+
+impl SourceMap {
+    pub(crate) fn insert(&mut self, list: &Rc<List<Expression>>, span: Span) {
+        self.spans
+            .insert(FormId::of(list), (Rc::downgrade(list), span));
+    }
+
+    pub(crate) fn get(&self, id: FormId) -> Option<Span> {
+        match self.spans.get(&id) {
+            Some((list, span)) if list.strong_count() > 0 => Some(*span),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::list;
+
+    #[test]
+    fn line_index() {
+        let index = LineIndex::new("ab\nгд e\n");
+
+        assert_eq!(
+            Span {
+                start: 0,
+                end: 1,
+                line: 1,
+                column: 1
+            },
+            index.span(0, 1)
+        );
+        assert_eq!(
+            Span {
+                start: 8,
+                end: 9,
+                line: 2,
+                column: 4
+            },
+            index.span(8, 9)
+        );
+        assert_eq!(3, index.span(10, 10).line);
+    }
+
+    #[test]
+    fn source_map_forgets_dropped_lists() {
+        let mut source_map = SourceMap::default();
+        let span = LineIndex::new("()").span(0, 2);
+        let list = Rc::new(list![]);
+        let id = FormId::of(&list);
+
+        source_map.insert(&list, span);
+
+        assert_eq!(Some(span), source_map.get(id));
+
+        drop(list);
+
+        assert_eq!(None, source_map.get(id));
+    }
+}
@@ -1,8 +1,16 @@
+use crate::read::source::{LineIndex, SourceError, Span};
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::Zero;
+
 #[derive(Debug, PartialEq)]
 pub enum Token {
     Symbol(String),
+    Keyword(String),
     String(String),
     Int64(i64),
+    BigInt(BigInt),
+    Ratio(BigRational),
     Float64(f64),
     Boolean(bool),
     LeftParen,
@@ -13,11 +21,21 @@ pub enum Token {
     RightSquareBracket,
     Caret,
     SingleQuote,
+    Backquote,
+    Comma,
+    CommaAt,
     Sharp,
     Dot,
     Nil,
 }
 
+/// Token along with its location in the source.
+#[derive(Debug, PartialEq)]
+pub(crate) struct SpannedToken {
+    pub(crate) token: Token,
+    pub(crate) span: Span,
+}
+
 enum TokenizerState {
     Outside,
     InsideString,
@@ -25,132 +43,313 @@ enum TokenizerState {
     InsideComment,
 }
 
-pub(crate) fn tokenize<I>(mut source_iter: I) -> Result<Vec<Token>, String>
-where
-    I: Iterator<Item = char>,
-{
+/// Whether the token is meant to be a number: a digit after an optional sign
+/// and decimal point, like `12`, `-0x1f` or `.5`.
+fn is_numeric(buff: &str) -> bool {
+    let unsigned = buff.strip_prefix(['-', '+']).unwrap_or(buff);
+    let unsigned = unsigned.strip_prefix('.').unwrap_or(unsigned);
+
+    unsigned.starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// Digits of the radix, which may be separated by single underscores like in
+/// `1_000_000`. Returns the digits without underscores.
+fn digits(buff: &str, radix: u32) -> Option<String> {
+    let is_separated = !buff.starts_with('_') && !buff.ends_with('_') && !buff.contains("__");
+
+    if buff.is_empty() || !is_separated || !buff.chars().all(|c| c == '_' || c.is_digit(radix)) {
+        return None;
+    }
+
+    Some(buff.replace('_', ""))
+}
+
+/// Integers that fit are `Int64`.
+fn integer_token(integer: BigInt) -> Token {
+    match i64::try_from(&integer) {
+        Ok(int) => Token::Int64(int),
+        Err(_) => Token::BigInt(integer),
+    }
+}
+
+/// Numeric literal with an optional sign: an integer like `1_000`, `0xff`,
+/// `0o17` or `0b1010`, a ratio like `2/3`, or a float like `1.5`, `.5` or
+/// `1e-3`. Ratios in lowest terms with denominator `1` are integers.
+fn parse_number(buff: &str) -> Option<Token> {
+    let (sign, unsigned) = match buff.strip_prefix('-') {
+        Some(unsigned) => ("-", unsigned),
+        None => ("", buff.strip_prefix('+').unwrap_or(buff)),
+    };
+
+    for (prefix, radix) in [("0x", 16), ("0o", 8), ("0b", 2)] {
+        if let Some(unsigned) = unsigned.strip_prefix(prefix) {
+            let integer = BigInt::parse_bytes(digits(unsigned, radix)?.as_bytes(), radix)?;
+
+            return Some(integer_token(if sign == "-" { -integer } else { integer }));
+        }
+    }
+
+    if let Some((numerator, denominator)) = unsigned.split_once('/') {
+        let numerator = format!("{}{}", sign, digits(numerator, 10)?).parse().ok()?;
+        let denominator: BigInt = digits(denominator, 10)?.parse().ok()?;
+
+        if denominator.is_zero() {
+            return None;
+        }
+
+        let ratio = BigRational::new(numerator, denominator);
+
+        return Some(if ratio.is_integer() {
+            integer_token(ratio.to_integer())
+        } else {
+            Token::Ratio(ratio)
+        });
+    }
+
+    let (mantissa, exponent) = match unsigned.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+        None => (unsigned, None),
+    };
+
+    let (whole, fraction) = match mantissa.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (mantissa, None),
+    };
+
+    if fraction.is_none() && exponent.is_none() {
+        return Some(integer_token(
+            format!("{}{}", sign, digits(whole, 10)?).parse().ok()?,
+        ));
+    }
+
+    // One of the whole and fractional parts may be omitted, like in `1.` or `.5`.
+    let whole = match whole {
+        "" => String::new(),
+        whole => digits(whole, 10)?,
+    };
+    let fraction = match fraction {
+        None | Some("") => String::new(),
+        Some(fraction) => digits(fraction, 10)?,
+    };
+
+    if whole.is_empty() && fraction.is_empty() {
+        return None;
+    }
+
+    let exponent = match exponent {
+        None => String::new(),
+        Some(exponent) => {
+            let (exponent_sign, exponent) = match exponent.strip_prefix('-') {
+                Some(exponent) => ("-", exponent),
+                None => ("", exponent.strip_prefix('+').unwrap_or(exponent)),
+            };
+
+            format!("e{}{}", exponent_sign, digits(exponent, 10)?)
+        }
+    };
+
+    format!("{}0{}.{}0{}", sign, whole, fraction, exponent)
+        .parse()
+        .ok()
+        .map(Token::Float64)
+}
+
+/// Float spelled out with `##`, as infinities and `NaN` have no digits.
+fn parse_symbolic_value(name: &str) -> Option<Token> {
+    match name {
+        "Inf" => Some(Token::Float64(f64::INFINITY)),
+        "-Inf" => Some(Token::Float64(f64::NEG_INFINITY)),
+        "NaN" => Some(Token::Float64(f64::NAN)),
+        _ => None,
+    }
+}
+
+pub(crate) fn tokenize(source: &str) -> Result<Vec<SpannedToken>, SourceError> {
     let spaces = " \t\n\r".to_owned();
-    let non_symbols = r#"(){}[]"'^#;"#.to_owned();
+    let non_symbols = r#"(){}[]"'`,^#;"#.to_owned();
+
+    let line_index = LineIndex::new(source);
+    let mut source_iter = source.char_indices();
 
     let mut tokens = vec![];
     let mut tokenizer_state = TokenizerState::Outside;
 
-    let mut buffered_char: Option<char> = None;
+    let mut buffered_char: Option<(usize, char)> = None;
+    // Start of a symbol read before it was known to be one, like `##` of `##Inf`.
+    let mut symbol_prefix = String::new();
+    let mut token_start = 0;
+
+    let spanned = |token: Token, start: usize, end: usize| SpannedToken {
+        token,
+        span: line_index.span(start, end),
+    };
 
     loop {
         match tokenizer_state {
             TokenizerState::Outside => {
-                match buffered_char.take().or_else(|| source_iter.next()) {
-                    Some('"') => {
+                let (offset, c) = match buffered_char.take().or_else(|| source_iter.next()) {
+                    Some(next) => next,
+                    None => {
+                        return Ok(tokens);
+                    }
+                };
+
+                token_start = offset;
+
+                let token = match c {
+                    '"' => {
                         tokenizer_state = TokenizerState::InsideString;
+                        continue;
                     }
-                    Some(c) if spaces.contains(c) => {
+                    c if spaces.contains(c) => {
                         // Skip delimiters
+                        continue;
                     }
-                    Some('(') => {
-                        tokens.push(Token::LeftParen);
-                    }
-                    Some(')') => {
-                        tokens.push(Token::RightParen);
-                    }
-                    Some('{') => {
-                        tokens.push(Token::LeftBracket);
-                    }
-                    Some('}') => {
-                        tokens.push(Token::RightBracket);
-                    }
-                    Some('[') => {
-                        tokens.push(Token::LeftSquareBracket);
-                    }
-                    Some(']') => {
-                        tokens.push(Token::RightSquareBracket);
-                    }
-                    Some('^') => {
-                        tokens.push(Token::Caret);
-                    }
-                    Some('#') => {
-                        tokens.push(Token::Sharp);
-                    }
-                    Some('\'') => {
-                        tokens.push(Token::SingleQuote);
-                    }
-                    Some(';') => {
+                    '(' => Token::LeftParen,
+                    ')' => Token::RightParen,
+                    '{' => Token::LeftBracket,
+                    '}' => Token::RightBracket,
+                    '[' => Token::LeftSquareBracket,
+                    ']' => Token::RightSquareBracket,
+                    '^' => Token::Caret,
+                    '#' => match source_iter.next() {
+                        // `#!` line of scripts.
+                        Some((_, '!')) if offset == 0 => {
+                            tokenizer_state = TokenizerState::InsideComment;
+                            continue;
+                        }
+                        Some((_, '#')) => {
+                            symbol_prefix = "##".to_owned();
+                            tokenizer_state = TokenizerState::InsideSymbol;
+                            continue;
+                        }
+                        next => {
+                            buffered_char = next;
+                            Token::Sharp
+                        }
+                    },
+                    '\'' => Token::SingleQuote,
+                    '`' => Token::Backquote,
+                    ',' => match source_iter.next() {
+                        Some((offset, '@')) => {
+                            tokens.push(spanned(Token::CommaAt, token_start, offset + 1));
+                            continue;
+                        }
+                        next => {
+                            buffered_char = next;
+                            Token::Comma
+                        }
+                    },
+                    ';' => {
                         tokenizer_state = TokenizerState::InsideComment;
+                        continue;
                     }
-                    Some(c) => {
-                        buffered_char.replace(c);
+                    c => {
+                        buffered_char.replace((offset, c));
                         tokenizer_state = TokenizerState::InsideSymbol;
+                        continue;
                     }
-                    None => {
-                        return Ok(tokens);
-                    }
-                }
+                };
+
+                tokens.push(spanned(token, offset, offset + c.len_utf8()));
             }
             TokenizerState::InsideString => {
                 let mut buff = String::new();
                 let mut escape = false;
 
-                loop {
+                let end = loop {
                     match source_iter.next() {
-                        Some('"') if escape == true => {
+                        Some((_, '"')) if escape => {
                             buff.push('"');
                             escape = false;
                         }
-                        Some('"') => {
-                            break;
+                        Some((offset, '"')) => {
+                            break offset + 1;
                         }
-                        Some('\\') => {
+                        Some((_, '\\')) => {
                             escape = true;
                         }
-                        Some(c) => {
+                        Some((_, c)) => {
                             buff.push(c);
                         }
-                        None => return Err("Unexpected end of input on reading string".to_owned()),
+                        None => {
+                            return Err(SourceError {
+                                span: line_index.span(token_start, source.len()),
+                                message: "Unexpected end of input on reading string".to_owned(),
+                            });
+                        }
                     }
-                }
+                };
 
-                tokens.push(Token::String(buff));
+                tokens.push(spanned(Token::String(buff), token_start, end));
 
                 tokenizer_state = TokenizerState::Outside;
             }
             TokenizerState::InsideSymbol => {
-                let mut buff = String::new();
+                let mut buff = std::mem::take(&mut symbol_prefix);
 
-                loop {
+                let end = loop {
                     match buffered_char.take().or_else(|| source_iter.next()) {
-                        Some(c) if spaces.contains(c) || non_symbols.contains(c) => {
-                            buffered_char.replace(c);
-                            break;
+                        Some((offset, c)) if spaces.contains(c) || non_symbols.contains(c) => {
+                            buffered_char.replace((offset, c));
+                            break offset;
                         }
-                        Some(c) => {
+                        Some((_, c)) => {
                             buff.push(c);
                         }
                         None => {
-                            break;
+                            break source.len();
                         }
                     }
-                }
+                };
 
-                if buff == "." {
-                    tokens.push(Token::Dot)
+                let token = if buff == "." {
+                    Token::Dot
                 } else if buff == "Nil" {
-                    tokens.push(Token::Nil)
+                    Token::Nil
                 } else if let Ok(bool) = buff.parse::<bool>() {
-                    tokens.push(Token::Boolean(bool));
-                } else if let Ok(integer) = buff.parse::<i64>() {
-                    tokens.push(Token::Int64(integer));
-                } else if let Ok(float) = buff.parse::<f64>() {
-                    tokens.push(Token::Float64(float));
+                    Token::Boolean(bool)
+                } else if let Some(name) = buff.strip_prefix("##") {
+                    match parse_symbolic_value(name) {
+                        Some(token) => token,
+                        None => {
+                            return Err(SourceError {
+                                span: line_index.span(token_start, end),
+                                message: format!("Unknown symbolic value: {}", buff),
+                            });
+                        }
+                    }
+                } else if is_numeric(&buff) {
+                    match parse_number(&buff) {
+                        // Infinities are only read as `##Inf` and `##-Inf`.
+                        Some(Token::Float64(float)) if float.is_infinite() => {
+                            return Err(SourceError {
+                                span: line_index.span(token_start, end),
+                                message: format!("Number is out of range: {}", buff),
+                            });
+                        }
+                        Some(token) => token,
+                        None => {
+                            return Err(SourceError {
+                                span: line_index.span(token_start, end),
+                                message: format!("Invalid number: {}", buff),
+                            });
+                        }
+                    }
+                } else if buff.len() > 1 && buff.starts_with(':') {
+                    Token::Keyword(buff[1..].to_string())
                 } else {
-                    tokens.push(Token::Symbol(buff));
-                }
+                    Token::Symbol(buff)
+                };
+
+                tokens.push(spanned(token, token_start, end));
 
                 tokenizer_state = TokenizerState::Outside;
             }
             TokenizerState::InsideComment => {
                 loop {
                     match source_iter.next() {
-                        Some('\n') | None => {
+                        Some((_, '\n')) | None => {
                             break;
                         }
                         Some(_) => {
@@ -167,6 +366,13 @@ where
 #[cfg(test)]
 mod tests {
     use super::{tokenize, Token};
+    use crate::read::source::Span;
+
+    fn tokens(program: &str) -> Result<Vec<Token>, String> {
+        tokenize(program)
+            .map(|tokens| tokens.into_iter().map(|t| t.token).collect())
+            .map_err(|e| e.to_string())
+    }
 
     #[test]
     fn read_whole_program() {
@@ -187,7 +393,108 @@ mod tests {
                 Token::Boolean(false),
                 Token::RightParen
             ]),
-            tokenize(program.chars())
+            tokens(program)
+        );
+    }
+
+    #[test]
+    fn read_keywords() {
+        assert_eq!(
+            Ok(vec![
+                Token::Keyword("name".to_owned()),
+                Token::Symbol(":".to_owned()),
+                Token::LeftBracket,
+                Token::RightBracket,
+            ]),
+            tokens(":name : {}")
+        );
+    }
+
+    #[test]
+    fn read_quote_prefixes() {
+        let program = r#"'a `(b ,c ,@d)"#;
+
+        assert_eq!(
+            Ok(vec![
+                Token::SingleQuote,
+                Token::Symbol("a".to_owned()),
+                Token::Backquote,
+                Token::LeftParen,
+                Token::Symbol("b".to_owned()),
+                Token::Comma,
+                Token::Symbol("c".to_owned()),
+                Token::CommaAt,
+                Token::Symbol("d".to_owned()),
+                Token::RightParen
+            ]),
+            tokens(program)
+        );
+    }
+
+    #[test]
+    fn read_token_spans() {
+        let spans = tokenize("(a\n  \"b\" ,@c)")
+            .unwrap()
+            .into_iter()
+            .map(|t| t.span)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                Span {
+                    start: 0,
+                    end: 1,
+                    line: 1,
+                    column: 1
+                },
+                Span {
+                    start: 1,
+                    end: 2,
+                    line: 1,
+                    column: 2
+                },
+                Span {
+                    start: 5,
+                    end: 8,
+                    line: 2,
+                    column: 3
+                },
+                Span {
+                    start: 9,
+                    end: 11,
+                    line: 2,
+                    column: 7
+                },
+                Span {
+                    start: 11,
+                    end: 12,
+                    line: 2,
+                    column: 9
+                },
+                Span {
+                    start: 12,
+                    end: 13,
+                    line: 2,
+                    column: 10
+                },
+            ],
+            spans
+        );
+    }
+
+    #[test]
+    fn read_unterminated_string() {
+        let error = tokenize("(a \"b").unwrap_err();
+
+        assert_eq!("Unexpected end of input on reading string", error.message);
+        assert_eq!(
+            (3, 5, 1, 4),
+            (
+                error.span.start,
+                error.span.end,
+                error.span.line,
+                error.span.column
+            )
         );
     }
 }
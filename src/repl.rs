@@ -1,24 +1,244 @@
-use crate::eval;
+//! Interactive session of the `iamlisp` binary.
+
+use crate::eval::create_env;
 use crate::eval::env::Env;
-use crate::read::parse;
+use crate::eval::types::Expression;
+use crate::interpreter::{eval_source, eval_source_with_stats};
+use crate::read::is_incomplete;
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::FileHistory;
+use rustyline::validate::{ValidationContext, ValidationResult, Validator};
+use rustyline::{Context, Editor, Helper};
+use std::path::PathBuf;
+use std::time::Instant;
+
+fn is_symbol_delimiter(c: char) -> bool {
+    c.is_whitespace() || r#"(){}[]"'`,@#;"#.contains(c)
+}
+
+/// Completes the symbol before the cursor with names bound in the environment.
+fn complete_symbol(env: &Env, line: &str, pos: usize) -> (usize, Vec<String>) {
+    let start = line[..pos]
+        .char_indices()
+        .rev()
+        .find(|(_, c)| is_symbol_delimiter(*c))
+        .map_or(0, |(i, c)| i + c.len_utf8());
+    let prefix = &line[start..pos];
+
+    if prefix.is_empty() {
+        return (pos, vec![]);
+    }
+
+    let mut names = env
+        .names()
+        .into_iter()
+        .filter(|name| name.starts_with(prefix))
+        .map(String::from)
+        .collect::<Vec<_>>();
+
+    names.sort();
+    names.dedup();
+
+    (start, names)
+}
+
+struct ReplHelper {
+    env: Env,
+}
+
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(complete_symbol(&self.env, line, pos))
+    }
+}
+
+/// Input is read until all lists and strings are closed.
+impl Validator for ReplHelper {
+    fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
+        if is_incomplete(ctx.input()) {
+            Ok(ValidationResult::Incomplete)
+        } else {
+            Ok(ValidationResult::Valid(None))
+        }
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+const HELP: &str = "Commands:
+  :help          Show this message
+  :env [all]     List bindings of the session, with natives if `all` is given
+  :load <file>   Evaluate the file in the session
+  :reset         Start the session over with a fresh environment
+  :time <expr>   Evaluate the expression and show time and steps taken
+  :type <expr>   Evaluate the expression and show the type of its result";
+
+/// Natives live in the root environment and the session binds into its child,
+/// so they can be listed separately.
+fn session_env() -> Env {
+    create_env().child()
+}
+
+fn list_bindings(env: &Env, all: bool) -> String {
+    let mut bindings = env.bindings();
+    let mut parent = env.parent();
+
+    while let (true, Some(env)) = (all, parent) {
+        bindings.extend(env.bindings());
+        parent = env.parent();
+    }
 
-pub(crate) fn eval(program: &str, env: &Env) -> Result<String, String> {
-    let expression = parse(program).map_err(|e| e.to_string())?;
-    let result = eval::eval(&expression, &env).map_err(|e| e.to_string())?;
+    bindings.sort_by_key(|(name, _)| *name);
+    bindings.dedup_by_key(|(name, _)| *name);
 
-    Ok(format!("{}", result))
+    bindings
+        .into_iter()
+        .map(|(name, value)| format!("{} = {}", name, value))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Runs a `:command` of the session. Lines which are not known commands give
+/// `None` and are evaluated as usual, so `:keyword` still evaluates to itself.
+fn run_command(line: &str, env: &mut Env) -> Option<Result<String, String>> {
+    let line = line.trim();
+    let (command, argument) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let argument = argument.trim();
+
+    let output = match (command, argument) {
+        (":help", "") => Ok(HELP.to_string()),
+        (":env", "") => Ok(list_bindings(env, false)),
+        (":env", "all") => Ok(list_bindings(env, true)),
+        (":load", "") => Err("error: File is expected after :load".to_string()),
+        (":load", path) => match std::fs::read_to_string(path) {
+            Ok(program) => eval_source(path, &program, env)
+                .map(|result| result.to_string())
+                .map_err(|e| e.render(&program)),
+            Err(e) => Err(format!("error: Cannot read {}: {}", path, e)),
+        },
+        (":reset", "") => {
+            *env = session_env();
+
+            Ok(Expression::default().to_string())
+        }
+        (":time", "") | (":type", "") => {
+            Err(format!("error: Expression is expected after {}", command))
+        }
+        (":time", expression) => {
+            let start = Instant::now();
+
+            eval_source_with_stats("<repl>", expression, env)
+                .map(|(result, stats)| {
+                    format!(
+                        "{}\nTime: {:?}, steps: {}",
+                        result,
+                        start.elapsed(),
+                        stats.steps
+                    )
+                })
+                .map_err(|e| e.render(expression))
+        }
+        (":type", expression) => eval_source("<repl>", expression, env)
+            .map(|result| result.type_name().to_string())
+            .map_err(|e| e.render(expression)),
+        _ => return None,
+    };
+
+    Some(output)
+}
+
+fn history_path() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".iamlisp_history"))
+}
+
+/// Runs interactive read-eval-print loop until the end of input.
+pub fn run() -> rustyline::Result<()> {
+    let mut env = session_env();
+    let mut editor = Editor::<ReplHelper, FileHistory>::new()?;
+
+    editor.set_helper(Some(ReplHelper { env: env.clone() }));
+
+    let history = history_path();
+
+    if let Some(path) = &history {
+        // There is no history yet on the first run.
+        let _ = editor.load_history(path);
+    }
+
+    loop {
+        match editor.readline("iamlisp> ") {
+            Ok(line) if line.trim().is_empty() => (),
+            Ok(line) => {
+                editor.add_history_entry(line.as_str())?;
+
+                let output = run_command(&line, &mut env).unwrap_or_else(|| {
+                    eval_source("<repl>", &line, &env)
+                        .map(|result| result.to_string())
+                        .map_err(|e| e.render(&line))
+                });
+
+                match output {
+                    Ok(output) => println!("{}", output),
+                    Err(error) => eprintln!("{}", error),
+                }
+
+                // `:reset` replaces the environment completion works with.
+                if let Some(helper) = editor.helper_mut() {
+                    helper.env = env.clone();
+                }
+            }
+            Err(ReadlineError::Interrupted) => (),
+            Err(ReadlineError::Eof) => break,
+            Err(error) => return Err(error),
+        }
+    }
+
+    if let Some(path) = &history {
+        editor.save_history(path)?;
+    }
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::eval;
     use crate::eval::create_env;
+    use crate::read::parse;
+
+    fn eval(program: &str, env: &Env) -> Result<String, String> {
+        let expression = parse(program).map_err(|e| e.to_string())?;
+        let (result, _) = eval::eval_with_stats(&expression, env).map_err(|e| e.to_string())?;
+
+        Ok(format!("{}", result))
+    }
 
     #[test]
     fn test_primitives() {
         let table = vec![
             ("1", "1"),
             ("1.5", "1.5"),
+            ("3.0", "3.0"),
+            ("-0.0", "-0.0"),
+            ("1e21", "1e21"),
+            ("[1 1.0]", "[1 1.0]"),
             (r#""string""#, r#""string""#),
             (r#""string\"string""#, r#""string"string""#),
             ("true", "true"),
@@ -58,20 +278,329 @@ mod tests {
     fn test_math() {
         let table = vec![
             ("(+ 1 2)", "3"),
-            ("(+ 2.5 3.5)", "6"),
+            ("(+ 2.5 3.5)", "6.0"),
             ("(- 10 6)", "4"),
-            ("(- 10.5 3.5)", "7"),
+            ("(- 10.5 3.5)", "7.0"),
             ("(* 2 3)", "6"),
             ("(* 2.5 3.5)", "8.75"),
             ("(/ 10 2)", "5"),
             ("(/ 10.0 4.0)", "2.5"),
+            ("(/ 1.0 0.0)", "##Inf"),
+            ("(pow 2 10)", "1024"),
+            ("(pow 2 -2)", "0.25"),
+            ("(pow 2.0 0.5)", "1.4142135623730951"),
+            ("(- 9223372036854775807)", "-9223372036854775807"),
+            ("(+ 1 2.5)", "3.5"),
+            ("(+ 2.5 1)", "3.5"),
+            ("(+ 1 2 0.5)", "3.5"),
+            ("(- 10 0.5)", "9.5"),
+            ("(* 2 1.25)", "2.5"),
+            ("(/ 7 2)", "7/2"),
+            ("(quot 7 2)", "3"),
+            ("(/ 7 2.0)", "3.5"),
+            ("(/ 1 0.5)", "2.0"),
+            ("(pow 2 0.5)", "1.4142135623730951"),
+            ("(pow 2.5 2)", "6.25"),
+            ("(+)", "0"),
+            ("(*)", "1"),
         ];
         let env = create_env();
 
         for (program, expected_result) in table {
             let result = eval(program, &env).unwrap();
 
-            assert_eq!(result, expected_result);
+            assert_eq!(result, expected_result, "{}", program);
+        }
+
+        let errors = vec![
+            ("(/ 1 0)", "division by zero"),
+            ("(/ 10 2 0)", "division by zero"),
+            ("(pow 2 4294967296)", "Exponent is too large: 4294967296"),
+            (
+                "(pow 2 2000000000)",
+                "Result of pow is too large: 2 ^ 2000000000",
+            ),
+            (
+                "(pow 2/3 100000000)",
+                "Result of pow is too large: 2/3 ^ 100000000",
+            ),
+            (
+                r#"(+ 1 "2")"#,
+                r#"Function not implemented for this kind of arguments: (1 "2")"#,
+            ),
+            ("(/)", "expected at least 1 argument, got 0"),
+            ("(pow 2)", "expected 2 arguments, got 1"),
+        ];
+
+        for (program, expected_error) in errors {
+            assert_eq!(
+                Err(expected_error.to_string()),
+                eval(program, &env),
+                "{}",
+                program
+            );
+        }
+
+        assert_eq!(
+            Ok(r#""division by zero""#.to_string()),
+            eval("(try (/ 1 0) (catch e (error-message e)))", &env)
+        );
+    }
+
+    #[test]
+    fn test_big_integers() {
+        let table = vec![
+            ("9223372036854775807", "9223372036854775807"),
+            ("9223372036854775808", "9223372036854775808"),
+            ("-9223372036854775809", "-9223372036854775809"),
+            ("(+ 9223372036854775807 1)", "9223372036854775808"),
+            ("(- -9223372036854775807 2)", "-9223372036854775809"),
+            ("(- (- -9223372036854775807 1))", "9223372036854775808"),
+            ("(* 4611686018427387904 2)", "9223372036854775808"),
+            ("(/ (- -9223372036854775807 1) -1)", "9223372036854775808"),
+            ("(pow 2 64)", "18446744073709551616"),
+            ("(pow 1 2000000000)", "1"),
+            ("(pow -1 2000000001)", "-1"),
+            ("(pow 0 2000000000)", "0"),
+            ("(pow 2 63)", "9223372036854775808"),
+            ("(inc 9223372036854775807)", "9223372036854775808"),
+            ("(dec -9223372036854775809)", "-9223372036854775810"),
+            ("(abs -9223372036854775809)", "9223372036854775809"),
+            ("(- 9223372036854775808 1)", "9223372036854775807"),
+            ("(/ 18446744073709551616 4)", "4611686018427387904"),
+            ("(// -18446744073709551617 2)", "-9223372036854775809"),
+            ("(% 18446744073709551617 10)", "7"),
+            ("(mod -18446744073709551617 10)", "3"),
+            ("(+ 9223372036854775808 0.5)", "9.223372036854776e18"),
+            ("(max 1 9223372036854775808)", "9223372036854775808"),
+            ("(= 9223372036854775808 (+ 9223372036854775807 1))", "true"),
+            ("(= (- 9223372036854775808 1) 9223372036854775807)", "true"),
+            ("(= 9223372036854775808 9223372036854775808.0)", "true"),
+            ("(< 9223372036854775807 9223372036854775808)", "true"),
+            ("(> -9223372036854775809 -9223372036854775808)", "false"),
+            (
+                "(has #{(+ 9223372036854775807 1)} 9223372036854775808)",
+                "true",
+            ),
+        ];
+        let env = create_env();
+
+        eval(
+            "(defun factorial (n) (loop (acc 1 i n) (cond (<= i 1) acc (recur (* acc i) (dec i)))))",
+            &env,
+        )
+        .unwrap();
+
+        for (program, expected_result) in table {
+            assert_eq!(
+                Ok(expected_result.to_string()),
+                eval(program, &env),
+                "{}",
+                program
+            );
+        }
+
+        assert_eq!(
+            Ok("30414093201713378043612608166064768844377641568960512000000000000".to_string()),
+            eval("(factorial 50)", &env)
+        );
+        assert_eq!(
+            Ok("120".to_string()),
+            eval("(/ (factorial 30) (/ (factorial 30) 120))", &env)
+        );
+
+        let mut env = session_env();
+
+        for (program, expected_type) in [
+            ("(pow 2 64)", "BigInt"),
+            ("(- (pow 2 64) (pow 2 64))", "Int64"),
+            ("(* 4294967296 4294967296)", "BigInt"),
+            ("(/ (pow 2 64) 2)", "BigInt"),
+            ("(/ (pow 2 64) 4)", "Int64"),
+        ] {
+            assert_eq!(
+                Some(Ok(expected_type.to_string())),
+                run_command(&format!(":type {}", program), &mut env)
+            );
+        }
+    }
+
+    #[test]
+    fn test_ratios() {
+        let table = vec![
+            ("1/3", "1/3"),
+            ("-2/4", "-1/2"),
+            ("4/2", "2"),
+            ("(/ 1 3)", "1/3"),
+            ("(/ 6 -4)", "-3/2"),
+            ("(/ 1 2 3)", "1/6"),
+            ("(+ 1/3 2/3)", "1"),
+            ("(+ 1/2 1)", "3/2"),
+            ("(* 2/3 3/4)", "1/2"),
+            ("(- 1/2)", "-1/2"),
+            ("(+ 1/2 0.25)", "0.75"),
+            ("(/ 1/2 0.5)", "1.0"),
+            ("(pow 2/3 2)", "4/9"),
+            ("(pow 4 1/2)", "2.0"),
+            ("(abs -1/2)", "1/2"),
+            ("(inc 1/2)", "3/2"),
+            ("(quot 7 2)", "3"),
+            ("(quot -7 2)", "-3"),
+            ("(quot 7/2 1/2)", "7"),
+            ("(quot 7.5 2)", "3.0"),
+            ("(// -7/2 1)", "-4"),
+            ("(% 7/2 1)", "1/2"),
+            ("(mod -1/2 1)", "1/2"),
+            ("(floor 7/2)", "3"),
+            ("(ceil 7/2)", "4"),
+            ("(round -7/2)", "-4"),
+            ("(numerator 6/4)", "3"),
+            ("(denominator 6/4)", "2"),
+            ("(numerator 5)", "5"),
+            ("(denominator 5)", "1"),
+            ("(float 1/4)", "0.25"),
+            ("(float 3)", "3.0"),
+            ("(= 1/2 0.5)", "true"),
+            ("(= 2/2 1)", "true"),
+            ("(< 1/3 0.34 1/2)", "true"),
+            ("(max 1/3 1/4)", "1/3"),
+            ("(/ 18446744073709551616 3)", "18446744073709551616/3"),
+        ];
+        let env = create_env();
+
+        for (program, expected) in table {
+            assert_eq!(Ok(expected.to_string()), eval(program, &env), "{}", program);
+        }
+
+        let errors = vec![
+            ("(/ 1/2 0)", "division by zero"),
+            ("(quot 1 0)", "division by zero"),
+            (
+                "(numerator 0.5)",
+                "Numerator of Float64 is not defined: (0.5)",
+            ),
+        ];
+
+        for (program, expected) in errors {
+            assert_eq!(
+                Err(expected.to_string()),
+                eval(program, &env),
+                "{}",
+                program
+            );
+        }
+
+        let mut env = session_env();
+
+        for (program, expected_type) in [
+            ("1/3", "Ratio"),
+            ("(/ 4 2)", "Int64"),
+            ("(* 1/2 2)", "Int64"),
+            ("(/ 1 2.0)", "Float64"),
+        ] {
+            assert_eq!(
+                Some(Ok(expected_type.to_string())),
+                run_command(&format!(":type {}", program), &mut env)
+            );
+        }
+    }
+
+    #[test]
+    fn test_math_functions() {
+        let table = vec![
+            ("(// 7 2)", "3"),
+            ("(// -7 2)", "-4"),
+            ("(// 7 -2)", "-4"),
+            ("(// -8 2)", "-4"),
+            ("(// 7.5 2)", "3.0"),
+            ("(% 7 3)", "1"),
+            ("(% -7 3)", "-1"),
+            ("(% 7 -3)", "1"),
+            ("(% 7.5 2)", "1.5"),
+            ("(mod 7 3)", "1"),
+            ("(mod -7 3)", "2"),
+            ("(mod 7 -3)", "-2"),
+            ("(mod -7.5 2)", "0.5"),
+            ("(min 3 1 2)", "1"),
+            ("(max 3 1 2)", "3"),
+            ("(max 3 2.5)", "3.0"),
+            ("(min 1.5 2)", "1.5"),
+            ("(max 1)", "1"),
+            ("(abs -3)", "3"),
+            ("(abs -3.5)", "3.5"),
+            ("(inc 1)", "2"),
+            ("(inc 1.5)", "2.5"),
+            ("(dec 1)", "0"),
+            ("(floor 2.7)", "2.0"),
+            ("(floor -2.5)", "-3.0"),
+            ("(ceil 2.1)", "3.0"),
+            ("(round 2.5)", "3.0"),
+            ("(round 7)", "7"),
+            ("(sqrt 16)", "4.0"),
+            ("(sqrt 2.25)", "1.5"),
+            ("(exp 0)", "1.0"),
+            ("(log 1)", "0.0"),
+            ("(sin 0)", "0.0"),
+            ("(cos 0)", "1.0"),
+            ("(tan 0)", "0.0"),
+            ("(asin 1)", "1.5707963267948966"),
+            ("(acos 1)", "0.0"),
+            ("(atan 0)", "0.0"),
+        ];
+        let env = create_env();
+
+        for (program, expected_result) in table {
+            assert_eq!(
+                Ok(expected_result.to_string()),
+                eval(program, &env),
+                "{}",
+                program
+            );
+        }
+
+        let errors = vec![
+            ("(// 1 0)", "division by zero"),
+            ("(% 1 0)", "division by zero"),
+            ("(mod 1 0)", "division by zero"),
+            ("(// 1)", "expected 2 arguments, got 1"),
+            ("(mod 1 2 3)", "expected 2 arguments, got 3"),
+            ("(abs)", "expected 1 argument, got 0"),
+            ("(inc 1 2)", "expected 1 argument, got 2"),
+            ("(floor 1 2)", "expected 1 argument, got 2"),
+            ("(sqrt 1 2)", "expected 1 argument, got 2"),
+            ("(min)", "expected at least 1 argument, got 0"),
+            (
+                r#"(max 1 "2")"#,
+                r#"Function not implemented for this kind of arguments: (1 "2")"#,
+            ),
+            (
+                r#"(sqrt "4")"#,
+                "expected Float64 at argument 1, got String",
+            ),
+        ];
+
+        for (program, expected_error) in errors {
+            assert_eq!(
+                Err(expected_error.to_string()),
+                eval(program, &env),
+                "{}",
+                program
+            );
+        }
+
+        let mut env = session_env();
+
+        for (program, expected_type) in [
+            ("(max 3 2.5)", "Float64"),
+            ("(min 3 4)", "Int64"),
+            ("(+ 1 2.0)", "Float64"),
+            ("(* 2.0 1)", "Float64"),
+            ("(- 3 1)", "Int64"),
+        ] {
+            assert_eq!(
+                Some(Ok(expected_type.to_string())),
+                run_command(&format!(":type {}", program), &mut env)
+            );
         }
     }
 
@@ -86,13 +615,64 @@ mod tests {
             ("(= 10.5 0.5)", "false"),
             (r#"(= "foo" "bar")"#, "false"),
             (r#"(= "foo" "foo")"#, "true"),
+            ("(= 1 1.0)", "true"),
+            ("(= 1 1.0 1)", "true"),
+            ("(!= 1 1.5)", "true"),
+            (r#"(= "a" 1)"#, "false"),
+            (r#"(!= "a" 1)"#, "true"),
+            ("(= Nil Nil)", "true"),
+            ("(= Nil false)", "false"),
+            ("(= :a :a)", "true"),
+            ("(= '(1 (2 [3])) (list 1 (list 2 [3.0])))", "true"),
+            ("(= '(1 2) '(1 2 3))", "false"),
+            ("(= '(1 2) [1 2])", "false"),
+            ("(= [1 [2]] [1.0 [2.0]])", "true"),
+            ("(= {:a [1]} {:a [1.0]})", "true"),
+            ("(= {:a 1} {:a 1 :b 2})", "false"),
+            ("(= #{1 [2]} #{[2] 1})", "true"),
+            ("(= car car)", "true"),
+            ("(< 1 2 3)", "true"),
+            ("(< 1 3 2)", "false"),
+            ("(> 2 1.5)", "true"),
+            ("(>= 2.0 2)", "true"),
+            ("(<= 1 1 2)", "true"),
+            (r#"(< "abc" "abd")"#, "true"),
+            ("(< 0.0 (/ 0.0 0.0))", "false"),
+            ("(= 99999999999999999999 99999999999999999999.0)", "false"),
+            ("(= 9007199254740993 9007199254740992.0)", "false"),
+            ("(> 9007199254740993 9007199254740992.0)", "true"),
+            ("(= 9007199254740992 9007199254740992.0)", "true"),
+            ("(< 9223372036854775807 9223372036854775807.0)", "true"),
+            ("(= 1/3 0.3333333333333333)", "false"),
+            ("(= 1/4 0.25)", "true"),
+            ("(< 99999999999999999999 ##Inf)", "true"),
+            ("(> -1/2 ##-Inf)", "true"),
+            ("(= {1 :a} {1.0 :a})", "false"),
+            ("(= #{1} #{1.0})", "false"),
         ];
         let env = create_env();
 
         for (program, expected_result) in table {
             let result = eval(program, &env).unwrap();
 
-            assert_eq!(result, expected_result);
+            assert_eq!(result, expected_result, "{}", program);
+        }
+
+        let errors = vec![
+            (
+                r#"(< 1 "a")"#,
+                r#"Cannot compare Int64 with String: 1 and "a""#,
+            ),
+            (
+                "(> [1] [2])",
+                "Cannot compare Vector with Vector: [1] and [2]",
+            ),
+            ("(<= Nil 1)", "Cannot compare Nil with Int64: Nil and 1"),
+            ("(= 1)", "Too few arguments given: (1)"),
+        ];
+
+        for (program, expected_error) in errors {
+            assert_eq!(Err(expected_error.to_string()), eval(program, &env));
         }
     }
 
@@ -144,6 +724,298 @@ mod tests {
         assert_eq!(result, "10")
     }
 
+    #[test]
+    fn test_macro_call() {
+        let env = create_env();
+
+        eval(
+            "(def backwards (macro (. body) (cons (quote begin) (reverse body))))",
+            &env,
+        )
+        .unwrap();
+        eval(
+            "(def unless (macro (test then else) (list (quote cond) test else then)))",
+            &env,
+        )
+        .unwrap();
+        eval(
+            "(def as-data (macro (form) (list (quote quote) form)))",
+            &env,
+        )
+        .unwrap();
+
+        assert_eq!(
+            eval("(backwards (def a 1) (def a 2) (list 1 2))", &env).unwrap(),
+            "Nil"
+        );
+        assert_eq!(eval("a", &env).unwrap(), "1");
+        assert_eq!(eval("(unless (> 1 2) 10 20)", &env).unwrap(), "10");
+        assert_eq!(eval("(unless (< 1 2) 10 20)", &env).unwrap(), "20");
+        assert_eq!(
+            eval("(as-data (undefined-fn 1 2))", &env).unwrap(),
+            "(undefined-fn 1 2)"
+        );
+        assert_eq!(
+            eval("((lambda (x) (unless (= x 0) (* x 2) Nil)) 21)", &env).unwrap(),
+            "42"
+        );
+        assert_eq!(
+            eval("(unless true)", &env).err(),
+            Some("Not enough values to fill-up all arguments".to_string())
+        );
+        assert_eq!(env.get("body"), None);
+        assert_eq!(env.get("form"), None);
+    }
+
+    #[test]
+    fn test_defun_and_defmacro() {
+        let env = create_env();
+
+        assert_eq!(eval("(defun sum (a b) (+ a b))", &env).unwrap(), "Nil");
+        assert_eq!(eval("(sum 2 3)", &env).unwrap(), "5");
+        assert_eq!(eval("sum", &env).unwrap(), "(lambda (a b) (+ a b))");
+        assert_eq!(eval("(doc sum)", &env).unwrap(), "Nil");
+
+        eval(
+            r#"(defun fact (n) "Computes factorial of n" (cond (<= n 1) 1 (* n (fact (dec n)))))"#,
+            &env,
+        )
+        .unwrap();
+
+        assert_eq!(eval("(fact 10)", &env).unwrap(), "3628800");
+        assert_eq!(
+            eval("(doc fact)", &env).unwrap(),
+            r#""Computes factorial of n""#
+        );
+        assert_eq!(
+            eval("fact", &env).unwrap(),
+            r#"(lambda (n) "Computes factorial of n" (cond (<= n 1) 1 (* n (fact (dec n)))))"#
+        );
+
+        eval(r#"(defun greeting () "hello")"#, &env).unwrap();
+
+        assert_eq!(eval("(greeting)", &env).unwrap(), r#""hello""#);
+        assert_eq!(eval("(doc greeting)", &env).unwrap(), "Nil");
+
+        eval(
+            r#"
+            (defun sum-to (n)
+              (defun go (i acc) (cond (> i n) acc (go (+ i 1) (+ acc i))))
+              (go 1 0))"#,
+            &env,
+        )
+        .unwrap();
+
+        assert_eq!(eval("(sum-to 10)", &env).unwrap(), "55");
+        assert_eq!(env.get("go"), None);
+
+        eval(
+            r#"(defmacro backwards (. body) "Evaluates body in reverse order" (cons (quote begin) (reverse body)))"#,
+            &env,
+        )
+        .unwrap();
+
+        assert_eq!(eval("(backwards 1 2 3)", &env).unwrap(), "1");
+        assert_eq!(
+            eval("(doc backwards)", &env).unwrap(),
+            r#""Evaluates body in reverse order""#
+        );
+
+        assert_eq!(
+            eval("(defun 10 () 1)", &env).err(),
+            Some("10 - is not valid defun name".to_string())
+        );
+        assert_eq!(
+            eval("(defmacro m x x)", &env).err(),
+            Some("Invalid defmacro arguments".to_string())
+        );
+    }
+
+    #[test]
+    fn test_macroexpand() {
+        let env = create_env();
+
+        eval(
+            "(def unless (macro (test then else) (list (quote cond) test else then)))",
+            &env,
+        )
+        .unwrap();
+        eval(
+            "(def when-not (macro (test then) (list (quote unless) test then Nil)))",
+            &env,
+        )
+        .unwrap();
+
+        let table = vec![
+            ("(macroexpand-1 (quote (+ 1 2)))", "(+ 1 2)"),
+            ("(macroexpand-1 10)", "10"),
+            ("(macroexpand-1 (quote (when-not a b)))", "(unless a b Nil)"),
+            ("(macroexpand (quote (when-not a b)))", "(cond a Nil b)"),
+            (
+                "(macroexpand (quote (+ (when-not a b) 1)))",
+                "(+ (when-not a b) 1)",
+            ),
+            (
+                "(macroexpand-all (quote (+ (when-not a b) 1)))",
+                "(+ (cond a Nil b) 1)",
+            ),
+            (
+                "(macroexpand-all (quote (when-not a (quote (when-not b c)))))",
+                "(cond a Nil (quote (when-not b c)))",
+            ),
+            (
+                "(macroexpand-all '(lambda (unless a b c) (unless a b c)))",
+                "(lambda (unless a b c) (cond a c b))",
+            ),
+            (
+                "(macroexpand-all '(macro (unless) unless))",
+                "(macro (unless) unless)",
+            ),
+            (
+                "(macroexpand-all '(defun f (unless a b c) (unless a b c)))",
+                "(defun f (unless a b c) (cond a c b))",
+            ),
+            (
+                "(macroexpand-all '(defmacro m (unless a b c) \"doc\" (unless a b c)))",
+                "(defmacro m (unless a b c) \"doc\" (cond a c b))",
+            ),
+            (
+                "(macroexpand-all '(def (unless a b c) (unless a b c)))",
+                "(def (unless a b c) (cond a c b))",
+            ),
+            (
+                "(macroexpand-all '(loop ((unless a b c) (unless a b c)) (unless a b c)))",
+                "(loop ((unless a b c) (cond a c b)) (cond a c b))",
+            ),
+            (
+                "(macroexpand-all '(try (unless a b c) (catch (unless a b c) (unless a b c)) (finally (unless a b c))))",
+                "(try (cond a c b) (catch (unless a b c) (cond a c b)) (finally (cond a c b)))",
+            ),
+            (
+                "(macroexpand-all '(quasiquote ((unless a b c) (unquote (unless a b c)))))",
+                "(quasiquote ((unless a b c) (unquote (cond a c b))))",
+            ),
+            (
+                "(macroexpand-all '`[(unless a b c) ,@(unless a b c)])",
+                "(quasiquote [(unless a b c) (unquote-splicing (cond a c b))])",
+            ),
+            (
+                "(macroexpand-all '`(a `(b ,(unless a b c) ,,(unless a b c))))",
+                "(quasiquote (a (quasiquote (b (unquote (unless a b c)) (unquote (unquote (cond a c b)))))))",
+            ),
+            (
+                "(macroexpand-all '[(unless a b c) {:k (unless a b c)} #{(unless a b c)}])",
+                "[(cond a c b) {:k (cond a c b)} #{(cond a c b)}]",
+            ),
+        ];
+
+        for (program, expected_result) in table {
+            assert_eq!(eval(program, &env).unwrap(), expected_result, "{}", program);
+        }
+
+        assert_eq!(
+            eval("(macroexpand)", &env).err(),
+            Some("Expected exactly one argument, got: ()".to_string())
+        );
+    }
+
+    #[test]
+    fn test_quote_syntax() {
+        let env = create_env();
+
+        eval("(def b 2 c (list 3 4))", &env).unwrap();
+
+        let table = vec![
+            ("'a", "a"),
+            ("'(1 2 3)", "(1 2 3)"),
+            ("''a", "(quote a)"),
+            ("'{:a b}", "{:a b}"),
+            ("(count '{:a b})", "1"),
+            ("'#{a}", "#{a}"),
+            ("`a", "a"),
+            ("`(a b)", "(a b)"),
+            ("`(a ,b ,@c)", "(a 2 3 4)"),
+            ("`(a (,b ,@c) ,@c)", "(a (2 3 4) 3 4)"),
+            ("`(a ,(+ b 1))", "(a 3)"),
+            ("`(1 `(2 ,(3 ,b)))", "(1 (quasiquote (2 (unquote (3 2)))))"),
+            ("`()", "()"),
+        ];
+
+        for (program, expected_result) in table {
+            assert_eq!(eval(program, &env).unwrap(), expected_result, "{}", program);
+        }
+
+        eval(
+            "(defmacro unless (test then else) `(cond ,test ,else ,then))",
+            &env,
+        )
+        .unwrap();
+        eval(
+            "(defmacro backwards (. body) (cons 'begin (reverse body)))",
+            &env,
+        )
+        .unwrap();
+
+        assert_eq!(eval("(unless (> b 1) 10 20)", &env).unwrap(), "20");
+        assert_eq!(
+            eval("(macroexpand '(unless a b c))", &env).unwrap(),
+            "(cond a c b)"
+        );
+        assert_eq!(eval("(backwards b c 'd)", &env).unwrap(), "2");
+        assert_eq!(
+            eval("`,@c", &env).err(),
+            Some(
+                "Unquote-splicing is allowed only inside of list: (unquote-splicing c)".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_eval_expression() {
+        let env = create_env();
+
+        eval("(def b 2 form '(+ b 1))", &env).unwrap();
+
+        let table = vec![
+            ("(eval 1)", "1"),
+            ("(eval 'b)", "2"),
+            ("(eval form)", "3"),
+            ("(eval (list '* b (eval form)))", "6"),
+            ("(eval ''(1 2))", "(1 2)"),
+            ("(eval '[b #{b} {:b b}])", "[2 #{2} {:b 2}]"),
+            ("((lambda (b) (eval form)) 10)", "11"),
+            (
+                "(eval (cons 'begin (reverse '((def c 1) (def c 2)))))",
+                "Nil",
+            ),
+            ("c", "1"),
+        ];
+
+        for (program, expected_result) in table {
+            assert_eq!(eval(program, &env).unwrap(), expected_result, "{}", program);
+        }
+
+        eval(
+            "(defun count-down (n) (cond (> n 0) (eval `(count-down ,(dec n))) :done))",
+            &env,
+        )
+        .unwrap();
+
+        assert_eq!(eval("(count-down 10000)", &env).unwrap(), ":done");
+        assert_eq!(
+            eval("(eval)", &env).err(),
+            Some("Too few parameters for special operator: EVAL".to_string())
+        );
+        assert_eq!(
+            eval("(eval 1 2)", &env).err(),
+            Some("Too many parameters for special operator: EVAL".to_string())
+        );
+        assert_eq!(
+            eval("(eval 'd)", &env).err(),
+            Some("Symbol d is not defined".to_string())
+        );
+    }
+
     #[test]
     fn test_def_expression() {
         let env = create_env();
@@ -173,20 +1045,536 @@ mod tests {
             eval("(def (d . e) 0)", &env).err(),
             Some("Unable to destruct non-list to symbols list: (d . e)".to_string())
         );
+        assert_eq!(
+            eval("(def f)", &env).err(),
+            Some("Value is expected for definition of f".to_string())
+        );
+
+        assert_eq!(eval("(list (def g 1) g)", &env).unwrap(), "(Nil 1)");
+    }
+
+    #[test]
+    fn test_loop_expression() {
+        let env = create_env();
+
+        let countdown = r#"
+            (loop (x 100)
+                  (print x)
+                  (cond (> x 0) (recur (dec x))))"#;
+
+        assert_eq!(eval(countdown, &env).unwrap(), "Nil");
+
+        eval(
+            r#"
+            (def fib (lambda (n)
+              (loop (x 0 y 1 i n)
+                (cond (<= i 0) x (recur y (+ x y) (dec i))))))"#,
+            &env,
+        )
+        .unwrap();
+
+        assert_eq!(eval("(fib 0)", &env).unwrap(), "0");
+        assert_eq!(eval("(fib 10)", &env).unwrap(), "55");
+        assert_eq!(eval("(fib 50)", &env).unwrap(), "12586269025");
+
+        assert_eq!(
+            eval(
+                "(loop (i 100000 acc 0) (cond (> i 0) (recur (dec i) (+ acc i)) acc))",
+                &env
+            )
+            .unwrap(),
+            "5000050000"
+        );
+        assert_eq!(eval("(loop (a 2 b (* a 3)) b)", &env).unwrap(), "6");
+        assert_eq!(env.get("a"), None);
+    }
+
+    #[test]
+    fn test_vector() {
+        let env = create_env();
+
+        eval("(def a 3 v [1 2 3])", &env).unwrap();
+
+        let table = vec![
+            ("[]", "[]"),
+            ("[1 2 3]", "[1 2 3]"),
+            ("[1 (+ 1 1) a]", "[1 2 3]"),
+            ("[[a] 'b]", "[[3] b]"),
+            ("(vector 1 2 a)", "[1 2 3]"),
+            ("(vec (list 1 2))", "[1 2]"),
+            ("(nth v 0)", "1"),
+            ("(count v)", "3"),
+            ("(conj v 4 5)", "[1 2 3 4 5]"),
+            ("(assoc v 1 20)", "[1 20 3]"),
+            ("(assoc v 3 4)", "[1 2 3 4]"),
+            ("(subvec v 1)", "[2 3]"),
+            ("(subvec v 0 2)", "[1 2]"),
+            ("v", "[1 2 3]"),
+            ("(= [1 2] [1 2])", "true"),
+            ("(= [1 2] [1 3])", "false"),
+            ("`[a ,a]", "[a 3]"),
+        ];
+
+        for (program, expected_result) in table {
+            assert_eq!(eval(program, &env).unwrap(), expected_result, "{}", program);
+        }
+
+        let table = vec![
+            ("(nth v 5)", "Index 5 is out of bounds for length 3"),
+            ("(assoc v 4 1)", "Index 4 is out of bounds for length 3"),
+            (
+                "(subvec v 2 1)",
+                "Start index 2 is greater than end index 1",
+            ),
+        ];
+
+        for (program, expected_error) in table {
+            assert_eq!(
+                eval(program, &env).err(),
+                Some(expected_error.to_string()),
+                "{}",
+                program
+            );
+        }
+    }
+
+    #[test]
+    fn test_map() {
+        let env = create_env();
+
+        eval(r#"(def a 3 m {:a 1 "b" 2})"#, &env).unwrap();
+
+        let table = vec![
+            ("{}", "{}"),
+            ("{:a 1}", "{:a 1}"),
+            ("{:a (+ 1 a)}", "{:a 4}"),
+            ("{(+ a 1) :b (+ 1 a) :b}", "{4 :b}"),
+            ("{a a}", "{3 3}"),
+            ("(hash-map :a 1)", "{:a 1}"),
+            ("(get m :a)", "1"),
+            (r#"(get m "b")"#, "2"),
+            ("(get m :c)", "Nil"),
+            ("(get m :c 0)", "0"),
+            ("(get {##NaN 1} ##NaN)", "1"),
+            ("(get (assoc {} (/ 0.0 0.0) 1) ##NaN)", "1"),
+            ("(get [1 2] 1)", "2"),
+            ("(has m :a)", "true"),
+            ("(has m :c)", "false"),
+            ("(assoc {} :a 1)", "{:a 1}"),
+            ("(dissoc m :a \"b\")", "{}"),
+            ("(count m)", "2"),
+            ("(keys {:a 1})", "(:a)"),
+            ("(vals {:a 1})", "(1)"),
+            ("(entries {:a 1})", "([:a 1])"),
+            ("(conj {} [:a 1])", "{:a 1}"),
+            ("(merge {:a 1} {:a 2} Nil)", "{:a 2}"),
+            ("(update {:a 1} :a (lambda (x y) (+ x y)) 10)", "{:a 11}"),
+            ("(update {} :a (lambda (x) x))", "{:a Nil}"),
+            ("(get {[1 {:a Nil}] 1} [1 {:a Nil}])", "1"),
+            ("(get {(list 1 2) :x} (list 1 2))", ":x"),
+            ("(get {true 1 Nil 2} Nil)", "2"),
+            (r#"(= m {"b" 2 :a 1})"#, "true"),
+            ("(= m (dissoc m :a))", "false"),
+            ("(= :a :a)", "true"),
+        ];
+
+        for (program, expected_result) in table {
+            assert_eq!(eval(program, &env).unwrap(), expected_result, "{}", program);
+        }
+
+        assert_eq!(
+            eval("(hash-map :a)", &env).err(),
+            Some("Expected pairs of keys and values: (:a)".to_string())
+        );
+    }
+
+    #[test]
+    fn test_set() {
+        let env = create_env();
+
+        eval("(def a 3 s #{1 2 3})", &env).unwrap();
+
+        let table = vec![
+            ("#{}", "#{}"),
+            ("#{1 1}", "#{1}"),
+            ("#{(+ a 1)}", "#{4}"),
+            ("#{(+ a 1) (+ 1 a) 4}", "#{4}"),
+            ("(hash-set :a :a)", "#{:a}"),
+            ("(= s #{3 2 1})", "true"),
+            ("(= s (set '(1 2 3 3 2 1)))", "true"),
+            ("(= s (set [3 1 2]))", "true"),
+            ("(= s (set (to-list s)))", "true"),
+            ("(count (vec s))", "3"),
+            ("(count s)", "3"),
+            ("(has s 2)", "true"),
+            ("(has s 4)", "false"),
+            ("(has #{[1 2]} [1 2])", "true"),
+            ("(= (add s 4 1) #{1 2 3 4})", "true"),
+            ("(= (conj s 4) #{1 2 3 4})", "true"),
+            ("(delete s 1 2)", "#{3}"),
+            ("(= (union s #{4} #{5}) #{1 2 3 4 5})", "true"),
+            ("(intersection s #{3 4})", "#{3}"),
+            ("(= (difference s #{3 4}) #{1 2})", "true"),
+            ("(subset? #{1 2} s)", "true"),
+            ("(subset? #{1 4} s)", "false"),
+            ("(subset? #{} #{})", "true"),
+            ("(count #{##NaN ##NaN})", "1"),
+            ("(count (hash-set ##NaN (/ 0.0 0.0)))", "1"),
+            ("(has #{##NaN} ##NaN)", "true"),
+            ("(count #{0.0 -0.0})", "1"),
+            ("(= ##NaN ##NaN)", "false"),
+        ];
+
+        for (program, expected_result) in table {
+            assert_eq!(eval(program, &env).unwrap(), expected_result, "{}", program);
+        }
+
+        assert_eq!(
+            eval("(union #{1} [1])", &env).err(),
+            Some("Expected sets as arguments, got: (#{1} [1])".to_string())
+        );
+    }
+
+    #[test]
+    fn test_completion() {
+        let env = create_env().child();
+
+        eval("(def map-a 1 map-b 2)", &env).unwrap();
+
+        assert_eq!(
+            (1, vec!["map-a".to_string(), "map-b".to_string()]),
+            complete_symbol(&env, "(map-", 5)
+        );
+        assert_eq!(
+            (
+                5,
+                vec![
+                    "macroexpand".to_string(),
+                    "macroexpand-1".to_string(),
+                    "macroexpand-all".to_string()
+                ]
+            ),
+            complete_symbol(&env, "(f '(macroe", 11)
+        );
+        assert_eq!((3, vec![]), complete_symbol(&env, "(f ", 3));
+        assert_eq!(
+            (1, vec!["vector".to_string()]),
+            complete_symbol(&env, "(vecto 1)", 6)
+        );
     }
 
-    // #[test]
-    fn test_cond_expression() {
+    #[test]
+    fn test_recur_errors() {
+        let env = create_env();
+
+        let table = vec![
+            (
+                "(recur 1)",
+                "Recur can only be used in tail position of loop",
+            ),
+            (
+                "(loop (x 1) (+ 1 (recur 2)))",
+                "Recur can only be used in tail position of loop",
+            ),
+            (
+                "(loop (x 1) (cond (recur 2) 1))",
+                "Recur can only be used in tail position of loop",
+            ),
+            (
+                "(loop (x 1) ((lambda () (recur 2))))",
+                "Recur can only be used in tail position of loop",
+            ),
+            (
+                "(loop (x 1) (recur 1 2))",
+                "Recur expects 1 arguments, but 2 given",
+            ),
+            (
+                "(loop (x) x)",
+                "Loop init definition should contain pairs of names and values",
+            ),
+        ];
+
+        for (program, expected_error) in table {
+            assert_eq!(
+                eval(program, &env).err(),
+                Some(expected_error.to_string()),
+                "{}",
+                program
+            );
+        }
+    }
+
+    #[test]
+    fn test_tail_calls() {
         let env = create_env();
 
         let program = r#"
-            (def fib-tail (lambda (n) 
-              (def iter (lambda (n x y)
-                (cond ((<= i 0) x) ((iter (dec i) y (+ x y))))))
+            (def fib-tail (lambda (n)
+              (def iter (lambda (i x y)
+                (cond (<= i 0) x (iter (dec i) y (+ x y)))))
               (iter n 0 1)))"#;
 
         eval(program, &env).unwrap();
 
-        let result = eval("(fib-tail 5)", &env).unwrap();
+        assert_eq!(eval("(fib-tail 5)", &env).unwrap(), "5");
+        assert_eq!(eval("(fib-tail 50)", &env).unwrap(), "12586269025");
+
+        let program = r#"
+            (defun is-even (n) (cond (= n 0) true (is-odd (dec n))))
+            (defun is-odd (n) (cond (= n 0) false (is-even (dec n))))"#;
+
+        eval(program, &env).unwrap();
+
+        assert_eq!(eval("(is-even 10001)", &env).unwrap(), "false");
+        assert_eq!(eval("(is-odd 10001)", &env).unwrap(), "true");
+        assert_eq!(
+            eval("(begin 1 (begin 2 (is-even 10000)))", &env).unwrap(),
+            "true"
+        );
+    }
+
+    #[test]
+    fn test_apply() {
+        let env = create_env();
+
+        let table = vec![
+            ("(apply + '(1 2 3))", Ok("6".to_string())),
+            ("(apply + 1 2 [3 4])", Ok("10".to_string())),
+            ("(apply (lambda () 1) Nil)", Ok("1".to_string())),
+            (
+                "(apply (lambda (a b) (list b a)) '(1 2))",
+                Ok("(2 1)".to_string()),
+            ),
+            ("(apply apply (list + '(1 2)))", Ok("3".to_string())),
+            (
+                "(apply 1 '(2))",
+                Err("Expression is not callable type: 1 (args: (2))".to_string()),
+            ),
+            (
+                "(apply +)",
+                Err("Expected function and list of arguments: (+)".to_string()),
+            ),
+        ];
+
+        for (program, expected) in table {
+            assert_eq!(expected, eval(program, &env), "{}", program);
+        }
+    }
+
+    #[test]
+    fn test_sequences() {
+        let env = create_env();
+
+        eval("(defun positive? (n) (> n 0))", &env).unwrap();
+
+        let table = vec![
+            ("(map inc '(1 2 3))", "(2 3 4)"),
+            ("(map inc [1 2 3])", "[2 3 4]"),
+            ("(map inc #{1})", "#{2}"),
+            ("(map + '(1 2 3) [10 20])", "(11 22)"),
+            ("(map inc Nil)", "()"),
+            ("(filter positive? '(1 -2 3))", "(1 3)"),
+            ("(filter positive? [-1 2])", "[2]"),
+            ("(reduce + '(1 2 3))", "6"),
+            ("(reduce + 10 [1 2 3])", "16"),
+            ("(reduce + 10 '())", "10"),
+            (
+                "(reduce (lambda (acc x) (cons x acc)) '() '(1 2 3))",
+                "(3 2 1)",
+            ),
+            ("(find positive? '(-1 2 3))", "2"),
+            ("(find positive? '(-1 -2))", "Nil"),
+            ("(some positive? [-1 2])", "true"),
+            ("(some positive? [])", "false"),
+            ("(every? positive? #{1 2})", "true"),
+            ("(every? positive? '(1 -2))", "false"),
+            ("(includes? '(1 2) 2)", "true"),
+            ("(includes? [1 2] 3)", "false"),
+            ("(includes? [1 2] 1.0)", "true"),
+            ("(includes? '([1] 2) [1.0])", "true"),
+            ("(includes? #{1} 1.0)", "false"),
+            ("(includes? #{:a} :a)", "true"),
+            (r#"(includes? "iamlisp" "lisp")"#, "true"),
+            ("(cons 1 '(2))", "(1 2)"),
+            ("(cons 1 [2 3])", "(1 2 3)"),
+            ("(cons 1 Nil)", "(1)"),
+            ("(car '(1 2))", "1"),
+            ("(first [1 2])", "1"),
+            ("(first '())", "Nil"),
+            ("(cdr '(1 2 3))", "(2 3)"),
+            ("(rest [1 2 3])", "(2 3)"),
+            ("(rest '())", "()"),
+            ("(append '(1) [2] '(3))", "(1 2 3)"),
+            ("(append [1] '(2))", "[1 2]"),
+            ("(append)", "()"),
+            ("(reverse [1 2 3])", "[3 2 1]"),
+            ("(length '(1 2 3))", "3"),
+            ("(length [])", "0"),
+            ("(nth '(1 2 3) 1)", "2"),
+            ("(take 2 '(1 2 3))", "(1 2)"),
+            ("(take 5 [1 2])", "[1 2]"),
+            ("(drop 2 '(1 2 3))", "(3)"),
+            ("(drop 1 [1 2])", "[2]"),
+            ("(range 3)", "(0 1 2)"),
+            ("(range 1 4)", "(1 2 3)"),
+            ("(range 10 0 -3)", "(10 7 4 1)"),
+            ("(range 3 1)", "()"),
+            ("(length (range 1000000))", "1000000"),
+            ("(count (vec (range 1000000)))", "1000000"),
+            ("(zip '(1 2 3) [:a :b])", "([1 :a] [2 :b])"),
+            ("(zip)", "()"),
+        ];
+
+        for (program, expected) in table {
+            assert_eq!(Ok(expected.to_string()), eval(program, &env), "{}", program);
+        }
+
+        let errors = vec![
+            ("(filter inc '(1))", "Predicate should return Bool, got: 2"),
+            (
+                "(reduce + '())",
+                "Reduce of empty collection without initial value",
+            ),
+            (
+                "(take -1 '(1))",
+                "Count should be non-negative Int64, got: -1",
+            ),
+            ("(range 1 2 0)", "Range step should not be zero"),
+            ("(map +)", "Expected function and collections: (+)"),
+            (
+                "(take 1 #{1})",
+                "Function not implemented for this kind of arguments: (1 #{1})",
+            ),
+        ];
+
+        for (program, expected) in errors {
+            assert_eq!(
+                Err(expected.to_string()),
+                eval(program, &env),
+                "{}",
+                program
+            );
+        }
+    }
+
+    #[test]
+    fn test_try() {
+        let env = create_env();
+
+        let table = vec![
+            ("(try (+ 1 2) (catch e 0))", Ok("3".to_string())),
+            (
+                "(try (car 1) (catch e (error-message e)))",
+                Ok(r#""Function not implemented for this kind of arguments: (1)""#.to_string()),
+            ),
+            (
+                "(try undefined (catch e (error-kind e)))",
+                Ok(":error".to_string()),
+            ),
+            (
+                "(try (throw 42) (catch e (error-data e)))",
+                Ok("42".to_string()),
+            ),
+            (
+                r#"(try (throw (error :missing "no key" {:key :a})) (catch e e))"#,
+                Ok(r#"#<error :missing "no key" {:key :a}>"#.to_string()),
+            ),
+            (
+                r#"(try (throw (error :missing "no key")) (catch e (error-kind e)))"#,
+                Ok(":missing".to_string()),
+            ),
+            ("(error? (error \"oops\"))", Ok("true".to_string())),
+            (
+                "(try 1 (catch e 2) 3)",
+                Err("Catch and finally should be the last in try: 3".to_string()),
+            ),
+            ("(throw \"oops\")", Err(r#"oops"#.to_string())),
+            ("(throw (error \"oops\"))", Err("oops".to_string())),
+            ("(try (throw 1) (catch e (throw 2)))", Err("2".to_string())),
+        ];
+
+        for (program, expected) in table {
+            assert_eq!(expected, eval(program, &env), "{}", program);
+        }
+    }
+
+    #[test]
+    fn test_callbacks_do_not_grow_rust_stack() {
+        let env = create_env();
+
+        let program = r#"
+            (defun depth (n)
+              (cond (= n 0) 0 (+ 1 (apply depth (list (dec n))))))
+            (defun nested-update (n)
+              (cond (= n 0) {:n 0} (update (nested-update (dec n)) :n (lambda (x) (+ x 1)))))
+            (defun nested-map (n)
+              (cond (= n 0) '(0) (map (lambda (x) (+ x 1)) (nested-map (dec n)))))"#;
+
+        eval(program, &env).unwrap();
+
+        assert_eq!(eval("(depth 10000)", &env).unwrap(), "10000");
+        assert_eq!(
+            eval("(get (nested-update 10000) :n)", &env).unwrap(),
+            "10000"
+        );
+        assert_eq!(eval("(nested-map 10000)", &env).unwrap(), "(10000)");
+    }
+
+    #[test]
+    fn test_commands() {
+        let mut env = session_env();
+
+        eval("(def x 1)", &env).unwrap();
+
+        assert_eq!(Some(Ok("x = 1".to_string())), run_command(":env", &mut env));
+        assert!(run_command(":env all", &mut env)
+            .unwrap()
+            .unwrap()
+            .contains("\nvector = "));
+        assert_eq!(
+            Some(Ok("Vector".to_string())),
+            run_command(":type [x 2]", &mut env)
+        );
+        assert_eq!(
+            Some(Ok("Lambda".to_string())),
+            run_command(":type (lambda (a) a)", &mut env)
+        );
+        assert!(run_command(":time (+ x 2)", &mut env)
+            .unwrap()
+            .unwrap()
+            .starts_with("3\nTime: "));
+        assert_eq!(
+            Some(Err("error: Expression is expected after :type".to_string())),
+            run_command(":type", &mut env)
+        );
+        assert_eq!(None, run_command(":keyword", &mut env));
+        assert_eq!(None, run_command("(+ 1 2)", &mut env));
+
+        run_command(":reset", &mut env).unwrap().unwrap();
+
+        assert_eq!(Some(Ok("".to_string())), run_command(":env", &mut env));
+        assert_eq!(Err("Symbol x is not defined".to_string()), eval("x", &env));
+    }
+
+    #[test]
+    fn test_load_command() {
+        let mut env = session_env();
+        let path = std::env::temp_dir().join(format!("iamlisp-load-{}.lisp", std::process::id()));
+
+        std::fs::write(
+            &path,
+            "#!/usr/bin/env iamlisp\n(defun twice (x) (* x 2))\n(twice 2)",
+        )
+        .unwrap();
+
+        let result = run_command(&format!(":load {}", path.display()), &mut env);
+
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(Some(Ok("4".to_string())), result);
+        assert_eq!(Ok("6".to_string()), eval("(twice 3)", &env));
+        assert!(run_command(":load missing.lisp", &mut env)
+            .unwrap()
+            .is_err());
     }
 }